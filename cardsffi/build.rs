@@ -0,0 +1,25 @@
+// Generate cardsffi.h from src/lib.rs into OUT_DIR
+//
+// The committed include/cardsffi.h is checked against it by a test.
+// To update it: CARDSFFI_UPDATE_HEADER=1 cargo build
+use std::env;
+
+fn main() {
+    let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out = env::var("OUT_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", dir)).unwrap();
+
+    let bindings = cbindgen::Builder::new()
+        .with_crate(&dir)
+        .with_config(config)
+        .generate()
+        .expect("unable to generate bindings");
+    bindings.write_to_file(format!("{}/cardsffi.h", out));
+    if env::var_os("CARDSFFI_UPDATE_HEADER").is_some() {
+        bindings.write_to_file(format!("{}/include/cardsffi.h", dir));
+    }
+
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=CARDSFFI_UPDATE_HEADER");
+}
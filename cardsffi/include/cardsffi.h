@@ -0,0 +1,178 @@
+#ifndef CARDSFFI_H
+#define CARDSFFI_H
+
+/* Generated by cbindgen from src/lib.rs. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Success
+ */
+#define DC_OK 0
+
+/**
+ * A required pointer is NULL
+ */
+#define DC_ERR_NULL -1
+
+/**
+ * Card code, suit, number or text is out of range
+ */
+#define DC_ERR_INVALID_CARD -2
+
+/**
+ * The same card appears twice
+ */
+#define DC_ERR_DUPLICATE -3
+
+/**
+ * Wrong number of cards, players or other arguments
+ */
+#define DC_ERR_COUNT -4
+
+/**
+ * Output buffer is too small
+ */
+#define DC_ERR_BUFFER -5
+
+/**
+ * Internal error (caught panic)
+ */
+#define DC_ERR_PANIC -99
+
+/**
+ * Rank values (weaker first)
+ */
+#define DC_RANK_HIGH_CARD 0
+
+#define DC_RANK_ONE_PAIR 1
+
+#define DC_RANK_TWO_PAIR 2
+
+#define DC_RANK_TRIPS 3
+
+#define DC_RANK_STRAIGHT 4
+
+#define DC_RANK_FLASH 5
+
+#define DC_RANK_FULL_HOUSE 6
+
+#define DC_RANK_QUADS 7
+
+#define DC_RANK_STRAIGHT_FLASH 8
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * (suit, number) => card code
+ *
+ * # Safety
+ * out_code must be a valid pointer.
+ */
+int32_t dc_encode(uint32_t suit, uint32_t number, uint32_t *out_code);
+
+/**
+ * card code => (suit, number)
+ *
+ * # Safety
+ * out_suit and out_number must be valid pointers.
+ */
+int32_t dc_decode(uint32_t code, uint32_t *out_suit, uint32_t *out_number);
+
+/**
+ * "As" => card code
+ *
+ * # Safety
+ * text must be a NUL-terminated string, out_code a valid pointer.
+ */
+int32_t dc_parse_card(const char *text, uint32_t *out_code);
+
+/**
+ * card code => "As" (NUL-terminated, buf_len must be >= 3)
+ *
+ * # Safety
+ * buf must be valid for buf_len bytes.
+ */
+int32_t dc_format_card(uint32_t code, char *buf, size_t buf_len);
+
+/**
+ * rank value => "Flash", ... (static string, NULL if invalid)
+ */
+const char *dc_rank_name(uint32_t rank);
+
+/**
+ * 5 cards => (rank, order)
+ * Compare (rank, order) lexicographically to compare hands.
+ *
+ * # Safety
+ * hand must be valid for len elements, out pointers must be valid.
+ */
+int32_t dc_calc_hand(const uint32_t *hand, size_t len, uint32_t *out_rank, uint32_t *out_order);
+
+/**
+ * 5..=7 cards => (rank, order) of the best 5 cards
+ * out_best (5 elements) receives the best 5 cards if not NULL.
+ *
+ * # Safety
+ * cards must be valid for len elements, out pointers must be valid
+ * (out_best may be NULL).
+ */
+int32_t dc_calc_best(const uint32_t *cards,
+                     size_t len,
+                     uint32_t *out_rank,
+                     uint32_t *out_order,
+                     uint32_t *out_best);
+
+/**
+ * Compare the best hands of a and b (5..=7 cards each)
+ * out_result: -1 (a < b), 0 (tie), 1 (a > b)
+ *
+ * # Safety
+ * a and b must be valid for their lengths, out_result must be valid.
+ */
+int32_t dc_compare(const uint32_t *a,
+                   size_t a_len,
+                   const uint32_t *b,
+                   size_t b_len,
+                   int32_t *out_result);
+
+/**
+ * Exhaustive hold'em equity
+ * holes: 2 * players cards, board: 0..=5 cards,
+ * out_equity: players elements
+ *
+ * # Safety
+ * Pointers must be valid for the given lengths.
+ */
+int32_t dc_equity(const uint32_t *holes,
+                  size_t players,
+                  const uint32_t *board,
+                  size_t board_len,
+                  double *out_equity);
+
+/**
+ * Monte Carlo equity of hole (2 cards) against random hands
+ * opponents: 1..=9, trials > 0, the same seed gives the same result.
+ *
+ * # Safety
+ * Pointers must be valid for the given lengths.
+ */
+int32_t dc_equity_vs_random(const uint32_t *hole,
+                            const uint32_t *board,
+                            size_t board_len,
+                            uint32_t opponents,
+                            uint32_t trials,
+                            uint64_t seed,
+                            double *out_equity);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CARDSFFI_H */
@@ -0,0 +1,417 @@
+//! C ABI of cardslib
+//!
+//! The header include/cardsffi.h is generated by cbindgen (see build.rs).
+//!
+//! Cards are the integer codes of cardslib (suit * 13 + number,
+//! number 0 = 2, ..., 12 = A, suit 0 = c, 1 = d, 2 = h, 3 = s).
+//! Every function returns DC_OK or a negative DC_ERR_* code and writes
+//! its results through out pointers. Panics never cross the boundary.
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use cardslib::cards::{self, Rank, CARDS_NUM, HAND_SIZE, NUMBER_NUM, SUIT_NUM};
+use cardslib::equity::{self, BOARD_SIZE, HOLE_SIZE};
+
+/// Success
+pub const DC_OK: i32 = 0;
+/// A required pointer is NULL
+pub const DC_ERR_NULL: i32 = -1;
+/// Card code, suit, number or text is out of range
+pub const DC_ERR_INVALID_CARD: i32 = -2;
+/// The same card appears twice
+pub const DC_ERR_DUPLICATE: i32 = -3;
+/// Wrong number of cards, players or other arguments
+pub const DC_ERR_COUNT: i32 = -4;
+/// Output buffer is too small
+pub const DC_ERR_BUFFER: i32 = -5;
+/// Internal error (caught panic)
+pub const DC_ERR_PANIC: i32 = -99;
+
+/// Rank values (weaker first)
+pub const DC_RANK_HIGH_CARD: u32 = 0;
+pub const DC_RANK_ONE_PAIR: u32 = 1;
+pub const DC_RANK_TWO_PAIR: u32 = 2;
+pub const DC_RANK_TRIPS: u32 = 3;
+pub const DC_RANK_STRAIGHT: u32 = 4;
+pub const DC_RANK_FLASH: u32 = 5;
+pub const DC_RANK_FULL_HOUSE: u32 = 6;
+pub const DC_RANK_QUADS: u32 = 7;
+pub const DC_RANK_STRAIGHT_FLASH: u32 = 8;
+
+type FfiResult = Result<(), i32>;
+
+// run f and convert Err and panic into an error code
+fn guard<F: FnOnce() -> FfiResult>(f: F) -> i32 {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => DC_OK,
+        Ok(Err(code)) => code,
+        Err(_) => DC_ERR_PANIC,
+    }
+}
+
+// (ptr, len) => slice (NULL is allowed only if len == 0)
+unsafe fn input<'a, T>(ptr: *const T, len: usize) -> Result<&'a [T], i32> {
+    if len == 0 {
+        return Ok(&[])
+    }
+    if ptr.is_null() {
+        return Err(DC_ERR_NULL)
+    }
+
+    Ok(slice::from_raw_parts(ptr, len))
+}
+
+unsafe fn output<'a, T>(ptr: *mut T) -> Result<&'a mut T, i32> {
+    ptr.as_mut().ok_or(DC_ERR_NULL)
+}
+
+// all codes are valid and distinct
+fn check_cards(codes: &[u32]) -> FfiResult {
+    let mut used = 0u64;
+    for &code in codes {
+        if code >= CARDS_NUM {
+            return Err(DC_ERR_INVALID_CARD)
+        }
+        if used & (1 << code) != 0 {
+            return Err(DC_ERR_DUPLICATE)
+        }
+        used |= 1 << code;
+    }
+
+    Ok(())
+}
+
+fn check_best_size(len: usize) -> FfiResult {
+    if (HAND_SIZE..=HAND_SIZE + 2).contains(&len) {
+        Ok(())
+    }
+    else {
+        Err(DC_ERR_COUNT)
+    }
+}
+
+/// (suit, number) => card code
+///
+/// # Safety
+/// out_code must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn dc_encode(suit: u32, number: u32, out_code: *mut u32) -> i32 {
+    guard(|| {
+        let out = output(out_code)?;
+        if suit >= SUIT_NUM || number >= NUMBER_NUM {
+            return Err(DC_ERR_INVALID_CARD)
+        }
+        *out = cards::encode(suit, number);
+        Ok(())
+    })
+}
+
+/// card code => (suit, number)
+///
+/// # Safety
+/// out_suit and out_number must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn dc_decode(code: u32, out_suit: *mut u32, out_number: *mut u32) -> i32 {
+    guard(|| {
+        let suit = output(out_suit)?;
+        let number = output(out_number)?;
+        if code >= CARDS_NUM {
+            return Err(DC_ERR_INVALID_CARD)
+        }
+        let (s, n) = cards::decode(code);
+        *suit = s;
+        *number = n;
+        Ok(())
+    })
+}
+
+/// "As" => card code
+///
+/// # Safety
+/// text must be a NUL-terminated string, out_code a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn dc_parse_card(text: *const c_char, out_code: *mut u32) -> i32 {
+    guard(|| {
+        let out = output(out_code)?;
+        if text.is_null() {
+            return Err(DC_ERR_NULL)
+        }
+        let text = CStr::from_ptr(text).to_str().map_err(|_| DC_ERR_INVALID_CARD)?;
+        *out = cards::from_text(text).ok_or(DC_ERR_INVALID_CARD)?;
+        Ok(())
+    })
+}
+
+/// card code => "As" (NUL-terminated, buf_len must be >= 3)
+///
+/// # Safety
+/// buf must be valid for buf_len bytes.
+#[no_mangle]
+pub unsafe extern "C" fn dc_format_card(code: u32, buf: *mut c_char, buf_len: usize) -> i32 {
+    guard(|| {
+        if buf.is_null() {
+            return Err(DC_ERR_NULL)
+        }
+        if code >= CARDS_NUM {
+            return Err(DC_ERR_INVALID_CARD)
+        }
+        let text = cards::to_text(code);
+        if buf_len < text.len() + 1 {
+            return Err(DC_ERR_BUFFER)
+        }
+        let dst = slice::from_raw_parts_mut(buf as *mut u8, text.len() + 1);
+        dst[..text.len()].copy_from_slice(text.as_bytes());
+        dst[text.len()] = 0;
+        Ok(())
+    })
+}
+
+/// rank value => "Flash", ... (static string, NULL if invalid)
+#[no_mangle]
+pub extern "C" fn dc_rank_name(rank: u32) -> *const c_char {
+    let name: &'static [u8] = match rank {
+        DC_RANK_HIGH_CARD => b"HighCard\0",
+        DC_RANK_ONE_PAIR => b"OnePair\0",
+        DC_RANK_TWO_PAIR => b"TwoPair\0",
+        DC_RANK_TRIPS => b"Trips\0",
+        DC_RANK_STRAIGHT => b"Straight\0",
+        DC_RANK_FLASH => b"Flash\0",
+        DC_RANK_FULL_HOUSE => b"FullHouse\0",
+        DC_RANK_QUADS => b"Quads\0",
+        DC_RANK_STRAIGHT_FLASH => b"StraightFlash\0",
+        _ => return std::ptr::null(),
+    };
+
+    name.as_ptr() as *const c_char
+}
+
+fn write_value(value: (Rank, u32), out_rank: &mut u32, out_order: &mut u32) {
+    *out_rank = value.0 as u32;
+    *out_order = value.1;
+}
+
+/// 5 cards => (rank, order)
+/// Compare (rank, order) lexicographically to compare hands.
+///
+/// # Safety
+/// hand must be valid for len elements, out pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn dc_calc_hand(
+    hand: *const u32, len: usize,
+    out_rank: *mut u32, out_order: *mut u32) -> i32
+{
+    guard(|| {
+        let hand = input(hand, len)?;
+        let (rank, order) = (output(out_rank)?, output(out_order)?);
+        if hand.len() != HAND_SIZE {
+            return Err(DC_ERR_COUNT)
+        }
+        check_cards(hand)?;
+        write_value(cards::calc_hand(hand), rank, order);
+        Ok(())
+    })
+}
+
+/// 5..=7 cards => (rank, order) of the best 5 cards
+/// out_best (5 elements) receives the best 5 cards if not NULL.
+///
+/// # Safety
+/// cards must be valid for len elements, out pointers must be valid
+/// (out_best may be NULL).
+#[no_mangle]
+pub unsafe extern "C" fn dc_calc_best(
+    cards: *const u32, len: usize,
+    out_rank: *mut u32, out_order: *mut u32, out_best: *mut u32) -> i32
+{
+    guard(|| {
+        let codes = input(cards, len)?;
+        let (rank, order) = (output(out_rank)?, output(out_order)?);
+        check_best_size(codes.len())?;
+        check_cards(codes)?;
+        write_value(cards::calc_best(codes), rank, order);
+        if !out_best.is_null() {
+            let best = slice::from_raw_parts_mut(out_best, HAND_SIZE);
+            best.copy_from_slice(&cards::calc_best_cards(codes));
+        }
+        Ok(())
+    })
+}
+
+/// Compare the best hands of a and b (5..=7 cards each)
+/// out_result: -1 (a < b), 0 (tie), 1 (a > b)
+///
+/// # Safety
+/// a and b must be valid for their lengths, out_result must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn dc_compare(
+    a: *const u32, a_len: usize,
+    b: *const u32, b_len: usize,
+    out_result: *mut i32) -> i32
+{
+    guard(|| {
+        let (a, b) = (input(a, a_len)?, input(b, b_len)?);
+        let out = output(out_result)?;
+        for hand in &[a, b] {
+            check_best_size(hand.len())?;
+            check_cards(hand)?;
+        }
+        *out = cards::calc_best(a).cmp(&cards::calc_best(b)) as i32;
+        Ok(())
+    })
+}
+
+/// Exhaustive hold'em equity
+/// holes: 2 * players cards, board: 0..=5 cards,
+/// out_equity: players elements
+///
+/// # Safety
+/// Pointers must be valid for the given lengths.
+#[no_mangle]
+pub unsafe extern "C" fn dc_equity(
+    holes: *const u32, players: usize,
+    board: *const u32, board_len: usize,
+    out_equity: *mut f64) -> i32
+{
+    guard(|| {
+        if !(2..=10).contains(&players) || board_len > BOARD_SIZE {
+            return Err(DC_ERR_COUNT)
+        }
+        let holes = input(holes, players * HOLE_SIZE)?;
+        let board = input(board, board_len)?;
+        if out_equity.is_null() {
+            return Err(DC_ERR_NULL)
+        }
+        check_cards(&[holes, board].concat())?;
+
+        let hands: Vec<[u32; HOLE_SIZE]> = holes.chunks(HOLE_SIZE)
+            .map(|h| [h[0], h[1]])
+            .collect();
+        let result = equity::equity(&hands, board);
+        slice::from_raw_parts_mut(out_equity, players).copy_from_slice(&result);
+        Ok(())
+    })
+}
+
+/// Monte Carlo equity of hole (2 cards) against random hands
+/// opponents: 1..=9, trials > 0, the same seed gives the same result.
+///
+/// # Safety
+/// Pointers must be valid for the given lengths.
+#[no_mangle]
+pub unsafe extern "C" fn dc_equity_vs_random(
+    hole: *const u32,
+    board: *const u32, board_len: usize,
+    opponents: u32, trials: u32, seed: u64,
+    out_equity: *mut f64) -> i32
+{
+    guard(|| {
+        if !(1..=9).contains(&opponents) || trials == 0 || board_len > BOARD_SIZE {
+            return Err(DC_ERR_COUNT)
+        }
+        let hole = input(hole, HOLE_SIZE)?;
+        let board = input(board, board_len)?;
+        let out = output(out_equity)?;
+        check_cards(&[hole, board].concat())?;
+
+        *out = equity::equity_vs_random(
+            &[hole[0], hole[1]], board, opponents as usize, trials, seed);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::ptr;
+
+    #[test]
+    fn encode_decode() {
+        let mut code = 0;
+        let (mut s, mut n) = (0, 0);
+        unsafe {
+            assert_eq!(dc_encode(3, 12, &mut code), DC_OK);
+            assert_eq!(code, 51);
+            assert_eq!(dc_decode(code, &mut s, &mut n), DC_OK);
+            assert_eq!((s, n), (3, 12));
+            assert_eq!(dc_encode(4, 0, &mut code), DC_ERR_INVALID_CARD);
+            assert_eq!(dc_decode(52, &mut s, &mut n), DC_ERR_INVALID_CARD);
+            assert_eq!(dc_encode(0, 0, ptr::null_mut()), DC_ERR_NULL);
+        }
+    }
+
+    #[test]
+    fn text() {
+        let mut code = 0;
+        let mut buf = [0 as c_char; 3];
+        unsafe {
+            let text = CString::new("Td").unwrap();
+            assert_eq!(dc_parse_card(text.as_ptr(), &mut code), DC_OK);
+            assert_eq!(code, cards::encode(1, 8));
+            assert_eq!(dc_format_card(code, buf.as_mut_ptr(), buf.len()), DC_OK);
+            assert_eq!(CStr::from_ptr(buf.as_ptr()).to_str(), Ok("Td"));
+            assert_eq!(dc_format_card(code, buf.as_mut_ptr(), 2), DC_ERR_BUFFER);
+
+            let text = CString::new("Tx").unwrap();
+            assert_eq!(dc_parse_card(text.as_ptr(), &mut code), DC_ERR_INVALID_CARD);
+
+            assert_eq!(CStr::from_ptr(dc_rank_name(DC_RANK_FLASH)).to_str(), Ok("Flash"));
+            assert!(dc_rank_name(9).is_null());
+        }
+    }
+
+    #[test]
+    fn calc() {
+        let (mut rank, mut order) = (0, 0);
+        let mut best = [0u32; 5];
+        // 2c 2d Ts Js Qs Ks As
+        let hand = [0, 13, 47, 48, 49, 50, 51];
+        unsafe {
+            assert_eq!(dc_calc_hand(hand.as_ptr(), 5, &mut rank, &mut order), DC_OK);
+            assert_eq!(rank, DC_RANK_ONE_PAIR);
+            assert_eq!(dc_calc_best(hand.as_ptr(), 7, &mut rank, &mut order, best.as_mut_ptr()), DC_OK);
+            assert_eq!(rank, DC_RANK_STRAIGHT_FLASH);
+            assert_eq!(best, [51, 50, 49, 48, 47]);
+            assert_eq!(dc_calc_best(hand.as_ptr(), 7, &mut rank, &mut order, ptr::null_mut()), DC_OK);
+
+            assert_eq!(dc_calc_hand(hand.as_ptr(), 4, &mut rank, &mut order), DC_ERR_COUNT);
+            let dup = [0, 0, 1, 2, 3];
+            assert_eq!(dc_calc_hand(dup.as_ptr(), 5, &mut rank, &mut order), DC_ERR_DUPLICATE);
+            let invalid = [0, 1, 2, 3, 100];
+            assert_eq!(dc_calc_hand(invalid.as_ptr(), 5, &mut rank, &mut order), DC_ERR_INVALID_CARD);
+            assert_eq!(dc_calc_hand(ptr::null(), 5, &mut rank, &mut order), DC_ERR_NULL);
+
+            let mut result = 0;
+            assert_eq!(dc_compare(hand.as_ptr(), 5, hand.as_ptr(), 7, &mut result), DC_OK);
+            assert_eq!(result, -1);
+        }
+    }
+
+    #[test]
+    fn equity() {
+        // AcAd vs KcKd on Ah Kh 2s 7s
+        let holes = [12, 25, 11, 24];
+        let board = [38, 37, 39, 44];
+        let mut result = [0.0; 2];
+        let mut value = 0.0;
+        unsafe {
+            assert_eq!(dc_equity(holes.as_ptr(), 2, board.as_ptr(), 4, result.as_mut_ptr()), DC_OK);
+            assert!((result[1] - 1.0 / 44.0).abs() < 1e-9);
+            assert_eq!(dc_equity(holes.as_ptr(), 1, board.as_ptr(), 4, result.as_mut_ptr()), DC_ERR_COUNT);
+            let dup = [12, 25, 12, 24];
+            assert_eq!(dc_equity(dup.as_ptr(), 2, ptr::null(), 0, result.as_mut_ptr()), DC_ERR_DUPLICATE);
+
+            assert_eq!(dc_equity_vs_random(holes.as_ptr(), ptr::null(), 0, 1, 2000, 1, &mut value), DC_OK);
+            assert!(value > 0.8 && value < 0.9);
+            assert_eq!(dc_equity_vs_random(holes.as_ptr(), ptr::null(), 0, 0, 2000, 1, &mut value), DC_ERR_COUNT);
+        }
+    }
+
+    #[test]
+    fn header() {
+        // run CARDSFFI_UPDATE_HEADER=1 cargo build if this fails
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/cardsffi.h"));
+        assert_eq!(include_str!("../include/cardsffi.h"), generated, "include/cardsffi.h is out of date");
+    }
+}
@@ -0,0 +1,144 @@
+// Betting structures: no-limit, pot-limit and fixed-limit
+//
+// A game engine asks its structure for the sizes a seat may bet or raise
+// to, and whether a raise reopens the action. The engine itself handles
+// stacks: a seat which can not afford the minimum may still go all-in,
+// and such an incomplete raise does not reopen the action for the seats
+// which have already acted.
+//
+// All amounts are "to" amounts of the current round (see holdem::Action).
+
+// the betting round seen from the seat to act
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BetState {
+    // the bet to call
+    pub current_bet: u64,
+    // the size of the last full bet or raise (the big blind at first)
+    pub min_raise: u64,
+    // full bets and raises in the round (the big blind counts preflop)
+    pub bets: u32,
+    // chips the seat needs to call
+    pub to_call: u64,
+    // all chips in the hand, including bets of the round
+    pub pot: u64,
+    // the round uses the big bet (fixed-limit)
+    pub big_bet: bool,
+}
+
+pub trait BettingStructure {
+    // min..=max to amount of a bet or raise, ignoring the stack
+    // None if no more raises are allowed
+    fn raise_range(&self, state: &BetState) -> Option<(u64, u64)>;
+
+    // a raise from one to amount to another is a full raise
+    // (seats which acted at from can raise again)
+    fn reopens(&self, state: &BetState, from: u64, to: u64) -> bool {
+        to - from >= state.min_raise
+    }
+}
+
+// any amount from the last raise size to the whole stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoLimit;
+
+impl BettingStructure for NoLimit {
+    fn raise_range(&self, state: &BetState) -> Option<(u64, u64)> {
+        Some((state.current_bet + state.min_raise, u64::MAX))
+    }
+}
+
+// at most the pot after calling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PotLimit;
+
+impl BettingStructure for PotLimit {
+    fn raise_range(&self, state: &BetState) -> Option<(u64, u64)> {
+        let min = state.current_bet + state.min_raise;
+        let max = state.current_bet + state.pot + state.to_call;
+
+        Some((min, max.max(min)))
+    }
+}
+
+// bets and raises of a fixed size, up to cap bets in a round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedLimit {
+    pub small_bet: u64,
+    pub big_bet: u64,
+    // bet + 3 raises with 4
+    pub cap: u32,
+}
+
+impl FixedLimit {
+    // the usual structure: big bet = 2 small bets, cap 4
+    pub fn new(small_bet: u64) -> Self {
+        FixedLimit { small_bet, big_bet: small_bet * 2, cap: 4 }
+    }
+
+    fn size(&self, state: &BetState) -> u64 {
+        if state.big_bet { self.big_bet } else { self.small_bet }
+    }
+}
+
+impl BettingStructure for FixedLimit {
+    fn raise_range(&self, state: &BetState) -> Option<(u64, u64)> {
+        if state.bets >= self.cap {
+            return None
+        }
+        // completing a bring-in or a short all-in is the first bet
+        // (a bring-in of a full bet is raised like a bet)
+        let size = self.size(state);
+        let to = if state.bets == 0 && state.current_bet < size { size } else { state.current_bet + size };
+
+        Some((to, to))
+    }
+
+    // an all-in of at least half a bet counts as a full raise
+    fn reopens(&self, state: &BetState, from: u64, to: u64) -> bool {
+        (to - from) * 2 >= self.size(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // preflop with blinds 1/2, the first seat to act
+    const PREFLOP: BetState = BetState {
+        current_bet: 2, min_raise: 2, bets: 1, to_call: 2, pot: 3, big_bet: false,
+    };
+
+    #[test]
+    fn no_limit() {
+        assert_eq!(NoLimit.raise_range(&PREFLOP), Some((4, u64::MAX)));
+        let state = BetState { current_bet: 10, min_raise: 8, bets: 2, ..PREFLOP };
+        assert_eq!(NoLimit.raise_range(&state), Some((18, u64::MAX)));
+        assert!(NoLimit.reopens(&state, 10, 18));
+        assert!(!NoLimit.reopens(&state, 10, 17));
+    }
+
+    #[test]
+    fn pot_limit() {
+        // the pot raise preflop is to 7
+        assert_eq!(PotLimit.raise_range(&PREFLOP), Some((4, 7)));
+        // 10 in the pot, bet 5: call 5, then raise 20 => to 25
+        let state = BetState { current_bet: 5, min_raise: 5, bets: 1, to_call: 5, pot: 15, big_bet: false };
+        assert_eq!(PotLimit.raise_range(&state), Some((10, 25)));
+    }
+
+    #[test]
+    fn fixed_limit() {
+        let limit = FixedLimit::new(2);
+        assert_eq!(limit.raise_range(&PREFLOP), Some((4, 4)));
+        let river = BetState { current_bet: 0, min_raise: 2, bets: 0, to_call: 0, pot: 20, big_bet: true };
+        assert_eq!(limit.raise_range(&river), Some((4, 4)));
+        let capped = BetState { current_bet: 8, bets: 4, ..PREFLOP };
+        assert_eq!(limit.raise_range(&capped), None);
+        // half a bet reopens
+        assert!(limit.reopens(&river, 4, 6));
+        assert!(!limit.reopens(&river, 4, 5));
+        // complete the bring-in of 1 to 2
+        let third = BetState { current_bet: 1, min_raise: 1, bets: 0, to_call: 1, pot: 8, big_bet: false };
+        assert_eq!(limit.raise_range(&third), Some((2, 2)));
+    }
+}
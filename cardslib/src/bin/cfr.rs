@@ -0,0 +1,42 @@
+// Solve Kuhn poker or Leduc hold'em with CFR+ (or vanilla CFR)
+//
+// usage: cfr <kuhn|leduc> [iterations] [--vanilla]
+//
+// Prints the average strategy of each info set, the game value of
+// player 0 and the exploitability.
+use std::env;
+use std::process;
+use cardslib::cfr::{exploitability, expected_value, Kuhn, Leduc, Solver, TwoPlayerGame};
+
+fn solve<G: TwoPlayerGame>(game: G, iterations: u64, vanilla: bool) {
+    let mut solver = if vanilla { Solver::new(game) } else { Solver::new_plus(game) };
+    solver.run(iterations);
+    let strategy = solver.average();
+
+    print!("{}", strategy);
+    println!("value: {:.6}", expected_value(solver.game(), &strategy));
+    println!("exploitability: {:.6}", exploitability(solver.game(), &strategy));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let vanilla = args.iter().any(|a| a == "--vanilla");
+    let rest: Vec<&String> = args[1..].iter().filter(|a| *a != "--vanilla").collect();
+    if rest.is_empty() || rest.len() > 2 {
+        eprintln!("usage: {} <kuhn|leduc> [iterations] [--vanilla]", args[0]);
+        process::exit(1);
+    }
+    let iterations = rest.get(1).map_or(1000, |s| s.parse().unwrap_or_else(|_| {
+        eprintln!("invalid number: {}", s);
+        process::exit(1);
+    }));
+
+    match rest[0].as_str() {
+        "kuhn" => solve(Kuhn, iterations, vanilla),
+        "leduc" => solve(Leduc, iterations, vanilla),
+        game => {
+            eprintln!("unknown game: {}", game);
+            process::exit(1);
+        }
+    }
+}
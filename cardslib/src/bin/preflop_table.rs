@@ -0,0 +1,48 @@
+// Generate the preflop equity table
+//
+// usage: preflop_table <output> [opponents] [trials] [seed]
+//   output: *.csv for CSV, otherwise binary (PreflopTable::load())
+//
+// The heads-up matrix is exhaustive and takes hours, so build with --release.
+use std::env;
+use std::fs::File;
+use std::io::BufWriter;
+use std::process;
+use cardslib::preflop::{PreflopTable, CLASS_NUM, MAX_OPPONENTS};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 || args.len() > 5 {
+        eprintln!("usage: {} <output> [opponents] [trials] [seed]", args[0]);
+        process::exit(1);
+    }
+    let arg = |i: usize, default: u64| -> u64 {
+        args.get(i).map_or(default, |s| s.parse().unwrap_or_else(|_| {
+            eprintln!("invalid number: {}", s);
+            process::exit(1);
+        }))
+    };
+    let output = &args[1];
+    let opponents = arg(2, MAX_OPPONENTS as u64) as usize;
+    let trials = arg(3, 100_000) as u32;
+    let seed = arg(4, 0);
+    if !(1..=MAX_OPPONENTS).contains(&opponents) {
+        eprintln!("opponents must be 1..={}", MAX_OPPONENTS);
+        process::exit(1);
+    }
+
+    let table = PreflopTable::generate(opponents, trials, seed, |rows| {
+        eprintln!("{}/{}", rows, CLASS_NUM);
+    });
+
+    let result = if output.ends_with(".csv") {
+        File::create(output).and_then(|f| table.write_csv(BufWriter::new(f)))
+    }
+    else {
+        table.save(output)
+    };
+    if let Err(e) = result {
+        eprintln!("{}: {}", output, e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,37 @@
+// Heads-up push/fold charts from a preflop table
+//
+// usage: pushfold <table> <stack> [ante] [iterations]
+//   table: binary file of preflop_table, stack and ante in big blinds
+//
+// Prints the push and call ranges as grids and as range text.
+use std::env;
+use std::process;
+use cardslib::preflop::PreflopTable;
+use cardslib::pushfold::PushFold;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 || args.len() > 5 {
+        eprintln!("usage: {} <table> <stack> [ante] [iterations]", args[0]);
+        process::exit(1);
+    }
+    let arg = |i: usize, default: f64| -> f64 {
+        args.get(i).map_or(default, |s| s.parse().unwrap_or_else(|_| {
+            eprintln!("invalid number: {}", s);
+            process::exit(1);
+        }))
+    };
+    let stack = arg(2, 10.0);
+    let ante = arg(3, 0.0);
+    let iterations = arg(4, 1000.0) as u32;
+    if ante < 0.0 || stack <= 1.0 + ante {
+        eprintln!("stack must be more than the big blind and ante");
+        process::exit(1);
+    }
+
+    let table = PreflopTable::load(&args[1]).unwrap_or_else(|e| {
+        eprintln!("{}: {}", args[1], e);
+        process::exit(1);
+    });
+    print!("{}", PushFold::from_table(&table, stack, ante, iterations));
+}
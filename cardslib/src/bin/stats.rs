@@ -0,0 +1,41 @@
+// Player statistics of PokerStars hand history files
+//
+// usage: stats <file>...
+//
+// Hands which can not be read are reported (file and line) and skipped.
+// Prints VPIP, PFR, 3-bet, AF, WTSD, W$SD, c-bet and bb/100 of each
+// player, then the chips won in each position.
+use std::env;
+use std::fs;
+use std::process;
+use cardslib::pokerstars::import;
+use cardslib::stats::StatsAggregator;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("usage: {} <file>...", args[0]);
+        process::exit(1);
+    }
+
+    let mut stats = StatsAggregator::new();
+    let mut errors = 0;
+    for path in args[1..].iter() {
+        let text = fs::read_to_string(path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path, e);
+            process::exit(1);
+        });
+        for hand in import(&text) {
+            match hand {
+                Ok(hand) => stats.add(&hand.history),
+                Err(error) => {
+                    eprintln!("{}: {}", path, error);
+                    errors += 1;
+                }
+            }
+        }
+    }
+
+    eprintln!("{} hands, {} skipped", stats.hands(), errors);
+    print!("{}", stats);
+}
@@ -0,0 +1,526 @@
+// Counterfactual regret minimization for small two-player zero-sum games
+//
+// A game is a tree of Nodes built on demand from its states (see
+// TwoPlayerGame). Kuhn poker and Leduc hold'em are included.
+//
+// Solver runs vanilla CFR or CFR+ (regrets floored at 0, linear averaging)
+// with alternating updates. The average strategy converges to a Nash
+// equilibrium, measured by exploitability: what best responses win on
+// average against it (0 at an equilibrium).
+//
+// Kuhn poker: J, Q, K, ante 1, one bet of 1 (value -1/18 for player 0)
+// Leduc hold'em: two each of J, Q, K, ante 1, a private card and a board
+//   card, bets of 2 then 4, at most 2 raises per round.
+use core::fmt;
+use alloc::collections::BTreeMap;
+use alloc::{format, vec, string::String, vec::Vec};
+
+pub enum Node<S> {
+    // the payoff of player 0 (player 1 gets the negative)
+    Terminal(f64),
+    // (probability, next state)
+    Chance(Vec<(f64, S)>),
+    // info: what the player knows, the same for states they can not tell apart
+    Decision { player: usize, info: String, children: Vec<(char, S)> },
+}
+
+pub trait TwoPlayerGame {
+    type State;
+
+    fn root(&self) -> Self::State;
+    fn node(&self, state: &Self::State) -> Node<Self::State>;
+}
+
+// action probabilities of each info set
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Strategy {
+    table: BTreeMap<String, Vec<(char, f64)>>,
+}
+
+impl Strategy {
+    pub fn get(&self, info: &str) -> Option<&[(char, f64)]> {
+        self.table.get(info).map(|v| v.as_slice())
+    }
+
+    // probability of the action, uniform for unknown info sets
+    fn prob(&self, info: &str, index: usize, count: usize) -> f64 {
+        match self.table.get(info) {
+            Some(probs) => probs[index].1,
+            None => 1.0 / count as f64,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[(char, f64)])> {
+        self.table.iter().map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+}
+
+// one line per info set: "K:pb  p 0.000  b 1.000"
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.table.keys().map(|k| k.len()).max().unwrap_or(0);
+        for (info, probs) in self.table.iter() {
+            write!(f, "{:<width$}", info, width = width)?;
+            for (action, p) in probs.iter() {
+                write!(f, "  {} {:.3}", action, p)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct InfoSet {
+    actions: Vec<char>,
+    regret: Vec<f64>,
+    strategy_sum: Vec<f64>,
+}
+
+impl InfoSet {
+    // regret matching
+    fn strategy(&self) -> Vec<f64> {
+        let positive: f64 = self.regret.iter().map(|r| r.max(0.0)).sum();
+        let n = self.regret.len();
+        if positive > 0.0 {
+            self.regret.iter().map(|r| r.max(0.0) / positive).collect()
+        }
+        else {
+            vec![1.0 / n as f64; n]
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Solver<G> {
+    game: G,
+    plus: bool,
+    iterations: u64,
+    infos: BTreeMap<String, InfoSet>,
+}
+
+impl<G: TwoPlayerGame> Solver<G> {
+    // vanilla CFR
+    pub fn new(game: G) -> Self {
+        Solver { game, plus: false, iterations: 0, infos: BTreeMap::new() }
+    }
+
+    pub fn new_plus(game: G) -> Self {
+        Solver { plus: true, ..Self::new(game) }
+    }
+
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    pub fn run(&mut self, iterations: u64) {
+        for _ in 0..iterations {
+            self.iterations += 1;
+            for player in 0..2 {
+                let root = self.game.root();
+                self.cfr(&root, player, 1.0, 1.0);
+            }
+        }
+    }
+
+    // the average strategy (the equilibrium approximation)
+    pub fn average(&self) -> Strategy {
+        let table = self.infos.iter()
+            .map(|(info, set)| {
+                let total: f64 = set.strategy_sum.iter().sum();
+                let n = set.actions.len();
+                let probs = set.actions.iter().zip(set.strategy_sum.iter())
+                    .map(|(&a, &s)| (a, if total > 0.0 { s / total } else { 1.0 / n as f64 }))
+                    .collect();
+                (info.clone(), probs)
+            })
+            .collect();
+
+        Strategy { table }
+    }
+
+    // reach: probability of the player's own actions / of others and chance
+    // => the expected payoff of the player
+    fn cfr(&mut self, state: &G::State, player: usize, reach: f64, others: f64) -> f64 {
+        match self.game.node(state) {
+            Node::Terminal(payoff) => if player == 0 { payoff } else { -payoff },
+            Node::Chance(outcomes) => outcomes.iter()
+                .map(|(p, next)| p * self.cfr(next, player, reach, others * p))
+                .sum(),
+            Node::Decision { player: actor, info, children } => {
+                let set = self.infos.entry(info.clone()).or_insert_with(|| InfoSet {
+                    actions: children.iter().map(|c| c.0).collect(),
+                    regret: vec![0.0; children.len()],
+                    strategy_sum: vec![0.0; children.len()],
+                });
+                let sigma = set.strategy();
+
+                if actor != player {
+                    return children.iter().zip(sigma.iter())
+                        .map(|((_, next), &p)| p * self.cfr(next, player, reach, others * p))
+                        .sum()
+                }
+
+                let values: Vec<f64> = children.iter().zip(sigma.iter())
+                    .map(|((_, next), &p)| self.cfr(next, player, reach * p, others))
+                    .collect();
+                let value: f64 = values.iter().zip(sigma.iter()).map(|(v, p)| v * p).sum();
+
+                let weight = if self.plus { self.iterations as f64 } else { 1.0 };
+                let set = self.infos.get_mut(&info).unwrap();
+                for (k, v) in values.iter().enumerate() {
+                    set.regret[k] += others * (v - value);
+                    if self.plus {
+                        set.regret[k] = set.regret[k].max(0.0);
+                    }
+                    set.strategy_sum[k] += weight * reach * sigma[k];
+                }
+
+                value
+            }
+        }
+    }
+}
+
+// the payoff of player 0 when both play the strategy
+pub fn expected_value<G: TwoPlayerGame>(game: &G, strategy: &Strategy) -> f64 {
+    fn walk<G: TwoPlayerGame>(game: &G, state: &G::State, strategy: &Strategy) -> f64 {
+        match game.node(state) {
+            Node::Terminal(payoff) => payoff,
+            Node::Chance(outcomes) => outcomes.iter().map(|(p, next)| p * walk(game, next, strategy)).sum(),
+            Node::Decision { info, children, .. } => children.iter().enumerate()
+                .map(|(k, (_, next))| strategy.prob(&info, k, children.len()) * walk(game, next, strategy))
+                .sum(),
+        }
+    }
+
+    walk(game, &game.root(), strategy)
+}
+
+// best response of player against the strategy of the other
+struct BestResponse<'a, G> {
+    game: &'a G,
+    strategy: &'a Strategy,
+    player: usize,
+    // chosen action of decided info sets
+    choice: BTreeMap<String, usize>,
+    // action values of the info sets being decided
+    values: BTreeMap<String, Vec<f64>>,
+}
+
+impl<G: TwoPlayerGame> BestResponse<'_, G> {
+    // depth: own decisions before the state, decide info sets at target depth
+    // => the payoff of the player (meaningful once all on the path are decided)
+    fn walk(&mut self, state: &G::State, others: f64, depth: usize, target: usize) -> f64 {
+        match self.game.node(state) {
+            Node::Terminal(payoff) => if self.player == 0 { payoff } else { -payoff },
+            Node::Chance(outcomes) => outcomes.iter()
+                .map(|(p, next)| p * self.walk(next, others * p, depth, target))
+                .sum(),
+            Node::Decision { player, info, children } if player != self.player => {
+                let n = children.len();
+                children.iter().enumerate()
+                    .map(|(k, (_, next))| {
+                        let p = self.strategy.prob(&info, k, n);
+                        p * self.walk(next, others * p, depth, target)
+                    })
+                    .sum()
+            }
+            Node::Decision { info, children, .. } => {
+                if let Some(&k) = self.choice.get(&info) {
+                    return self.walk(&children[k].1, others, depth + 1, target)
+                }
+                let values: Vec<f64> = children.iter()
+                    .map(|(_, next)| self.walk(next, others, depth + 1, target))
+                    .collect();
+                if depth == target {
+                    let sum = self.values.entry(info).or_insert_with(|| vec![0.0; values.len()]);
+                    for (s, v) in sum.iter_mut().zip(values) {
+                        *s += others * v;
+                    }
+                }
+
+                0.0
+            }
+        }
+    }
+
+    fn max_depth(&self, state: &G::State, depth: usize) -> usize {
+        match self.game.node(state) {
+            Node::Terminal(_) => 0,
+            Node::Chance(outcomes) => outcomes.iter().map(|(_, next)| self.max_depth(next, depth)).max().unwrap_or(0),
+            Node::Decision { player, children, .. } => {
+                let next_depth = if player == self.player { depth + 1 } else { depth };
+                let deeper = children.iter().map(|(_, next)| self.max_depth(next, next_depth)).max().unwrap_or(0);
+                deeper.max(next_depth)
+            }
+        }
+    }
+
+    // decide from the deepest info sets up
+    fn value(&mut self) -> f64 {
+        let root = self.game.root();
+        for target in (0..self.max_depth(&root, 0)).rev() {
+            self.values.clear();
+            self.walk(&root, 1.0, 0, target);
+            for (info, values) in core::mem::take(&mut self.values) {
+                let best = (0..values.len()).fold(0, |b, k| if values[k] > values[b] { k } else { b });
+                self.choice.insert(info, best);
+            }
+        }
+
+        self.walk(&root, 1.0, 0, usize::MAX)
+    }
+}
+
+// the payoff of player best responding to the strategy of the other
+pub fn best_response_value<G: TwoPlayerGame>(game: &G, strategy: &Strategy, player: usize) -> f64 {
+    assert!(player < 2);
+
+    let mut br = BestResponse {
+        game, strategy, player, choice: BTreeMap::new(), values: BTreeMap::new(),
+    };
+    br.value()
+}
+
+// the average gain of a best response against the strategy (0 = Nash)
+pub fn exploitability<G: TwoPlayerGame>(game: &G, strategy: &Strategy) -> f64 {
+    (best_response_value(game, strategy, 0) + best_response_value(game, strategy, 1)) / 2.0
+}
+
+const CARD_CHARS: [char; 3] = ['J', 'Q', 'K'];
+
+// cards (None before the deal) and actions: p (pass) or b (bet)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Kuhn;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KuhnState {
+    pub cards: Option<[usize; 2]>,
+    pub history: String,
+}
+
+impl TwoPlayerGame for Kuhn {
+    type State = KuhnState;
+
+    fn root(&self) -> KuhnState {
+        KuhnState { cards: None, history: String::new() }
+    }
+
+    fn node(&self, state: &KuhnState) -> Node<KuhnState> {
+        let cards = match state.cards {
+            Some(cards) => cards,
+            None => {
+                let deals = (0..3).flat_map(|a| (0..3).filter(move |&b| b != a).map(move |b| [a, b]));
+                return Node::Chance(deals
+                    .map(|cards| (1.0 / 6.0, KuhnState { cards: Some(cards), history: String::new() }))
+                    .collect())
+            }
+        };
+        // the higher card wins the pot
+        let showdown = |stake: f64| if cards[0] > cards[1] { stake } else { -stake };
+        match state.history.as_str() {
+            "pp" => Node::Terminal(showdown(1.0)),
+            "bb" | "pbb" => Node::Terminal(showdown(2.0)),
+            "bp" => Node::Terminal(1.0),
+            "pbp" => Node::Terminal(-1.0),
+            history => {
+                let player = history.len() % 2;
+                let children = ['p', 'b'].iter()
+                    .map(|&a| (a, KuhnState { cards: state.cards, history: format!("{}{}", history, a) }))
+                    .collect();
+                Node::Decision { player, info: format!("{}:{}", CARD_CHARS[cards[player]], history), children }
+            }
+        }
+    }
+}
+
+// ranks: 0 (J) ..= 2 (K), two cards of each in the deck
+// actions: f (fold), c (check/call), r (bet/raise), '/' between rounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Leduc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeducState {
+    pub cards: Option<[usize; 2]>,
+    pub board: Option<usize>,
+    pub history: String,
+}
+
+const LEDUC_ANTE: f64 = 1.0;
+const LEDUC_BETS: [f64; 2] = [2.0, 4.0];
+const LEDUC_RAISES: usize = 2;
+
+// a pair with the board beats any single card
+pub fn leduc_rank(card: usize, board: usize) -> usize {
+    if card == board { 3 + card } else { card }
+}
+
+impl Leduc {
+    fn deals() -> Vec<(f64, [usize; 2])> {
+        let mut deals = Vec::new();
+        for a in 0..3 {
+            for b in 0..3 {
+                // 2/6 * 1/5 for the same rank, 2/6 * 2/5 otherwise
+                let p = if a == b { 1.0 / 15.0 } else { 2.0 / 15.0 };
+                deals.push((p, [a, b]));
+            }
+        }
+        deals
+    }
+}
+
+impl TwoPlayerGame for Leduc {
+    type State = LeducState;
+
+    fn root(&self) -> LeducState {
+        LeducState { cards: None, board: None, history: String::new() }
+    }
+
+    fn node(&self, state: &LeducState) -> Node<LeducState> {
+        let cards = match state.cards {
+            Some(cards) => cards,
+            None => return Node::Chance(Leduc::deals().into_iter()
+                .map(|(p, cards)| (p, LeducState { cards: Some(cards), ..self.root() }))
+                .collect()),
+        };
+
+        // chips put in by each player
+        let mut committed = [LEDUC_ANTE; 2];
+        let rounds: Vec<&str> = state.history.split('/').collect();
+        for (round, actions) in rounds.iter().enumerate() {
+            for (k, a) in actions.chars().enumerate() {
+                let p = k % 2;
+                match a {
+                    'f' => return Node::Terminal(if p == 0 { -committed[0] } else { committed[1] }),
+                    'c' => committed[p] = committed[1 - p],
+                    _ => committed[p] = committed[1 - p] + LEDUC_BETS[round],
+                }
+            }
+        }
+
+        let round = rounds.len() - 1;
+        let actions = rounds[round];
+        let over = actions.len() >= 2 && actions.ends_with('c');
+        if over && round == 1 {
+            let board = state.board.unwrap();
+            let (a, b) = (leduc_rank(cards[0], board), leduc_rank(cards[1], board));
+            let payoff = match a.cmp(&b) {
+                core::cmp::Ordering::Greater => committed[1],
+                core::cmp::Ordering::Less => -committed[0],
+                core::cmp::Ordering::Equal => 0.0,
+            };
+            return Node::Terminal(payoff)
+        }
+        if over {
+            // the board card from the 4 left
+            return Node::Chance((0..3)
+                .filter_map(|board| {
+                    let left = 2 - cards.iter().filter(|&&c| c == board).count();
+                    (left > 0).then(|| (left as f64 / 4.0, LeducState {
+                        cards: state.cards,
+                        board: Some(board),
+                        history: format!("{}/", state.history),
+                    }))
+                })
+                .collect())
+        }
+
+        let player = actions.len() % 2;
+        let raises = actions.chars().filter(|&a| a == 'r').count();
+        let mut legal = Vec::with_capacity(3);
+        if raises > 0 {
+            legal.push('f');
+        }
+        legal.push('c');
+        if raises < LEDUC_RAISES {
+            legal.push('r');
+        }
+        let children = legal.into_iter()
+            .map(|a| (a, LeducState { history: format!("{}{}", state.history, a), ..state.clone() }))
+            .collect();
+        let board = state.board.map_or(String::new(), |b| CARD_CHARS[b].into());
+        let info = format!("{}{}:{}", CARD_CHARS[cards[player]], board, state.history);
+
+        Node::Decision { player, info, children }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prob(strategy: &Strategy, info: &str, action: char) -> f64 {
+        strategy.get(info).unwrap().iter().find(|a| a.0 == action).unwrap().1
+    }
+
+    #[test]
+    fn kuhn_cfr_plus() {
+        let mut solver = Solver::new_plus(Kuhn);
+        solver.run(2000);
+        let strategy = solver.average();
+        assert_eq!(strategy.iter().count(), 12);
+        assert!((expected_value(&Kuhn, &strategy) + 1.0 / 18.0).abs() < 1e-3);
+        assert!(exploitability(&Kuhn, &strategy) < 5e-3);
+        // player 1 calls a bet with K, folds J
+        assert!(prob(&strategy, "K:b", 'b') > 0.99);
+        assert!(prob(&strategy, "J:b", 'p') > 0.99);
+        // player 0 bets K three times as often as J
+        let ratio = prob(&strategy, "K:", 'b') / prob(&strategy, "J:", 'b');
+        assert!((ratio - 3.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn kuhn_vanilla() {
+        let mut solver = Solver::new(Kuhn);
+        solver.run(2000);
+        let strategy = solver.average();
+        assert!(exploitability(&Kuhn, &strategy) < 0.01);
+        assert_eq!(solver.iterations(), 2000);
+    }
+
+    #[test]
+    fn best_response() {
+        // the uniform strategy is far from an equilibrium
+        let mut solver = Solver::new(Kuhn);
+        solver.run(0);
+        let uniform = solver.average();
+        assert!(exploitability(&Kuhn, &uniform) > 0.1);
+        // any strategy: best responses win at least the game value
+        assert!(best_response_value(&Kuhn, &uniform, 0) >= -1.0 / 18.0);
+        assert!(best_response_value(&Kuhn, &uniform, 1) >= 1.0 / 18.0);
+    }
+
+    #[test]
+    fn leduc() {
+        assert!(leduc_rank(0, 0) > leduc_rank(2, 0));
+        assert!(leduc_rank(2, 1) > leduc_rank(1, 0));
+
+        let deals: f64 = Leduc::deals().iter().map(|d| d.0).sum();
+        assert!((deals - 1.0).abs() < 1e-12);
+        let mut solver = Solver::new_plus(Leduc);
+        solver.run(50);
+        let early = exploitability(&Leduc, &solver.average());
+        solver.run(250);
+        let strategy = solver.average();
+        assert_eq!(strategy.iter().count(), 288);
+        let late = exploitability(&Leduc, &strategy);
+        assert!(late < early && late < 0.08);
+        // the game value of player 0 is about -0.0856
+        assert!((expected_value(&Leduc, &strategy) + 0.0856).abs() < 0.005);
+    }
+
+    #[test]
+    fn display() {
+        let mut solver = Solver::new_plus(Kuhn);
+        solver.run(10);
+        let text = format!("{}", solver.average());
+        assert_eq!(text.lines().count(), 12);
+        assert!(text.lines().any(|l| l.starts_with("K:pb  p ")));
+    }
+}
@@ -0,0 +1,439 @@
+// Draw poker: five-card draw and 2-7 triple draw
+//
+// Five cards each, a betting round, then each draw (discard and replace
+// any cards, in order from the left of the button) is followed by another
+// betting round, and the showdown.
+//   five-card draw: one draw, the best hand by calc_hand() wins
+//   2-7 triple draw: three draws, the lowest hand by deuce_to_seven() wins
+// When the deck runs out, the cards discarded so far are shuffled into
+// a new deck (not the ones of the seat drawing).
+//
+// Blinds and betting are the same as holdem (see table::Table). With
+// FixedLimit, the latter half of the betting rounds use the big bet.
+use alloc::{vec, vec::Vec};
+use core::cmp::Reverse;
+use crate::betting::{BettingStructure, NoLimit};
+use crate::cards::{calc_hand, Rank, HAND_SIZE};
+use crate::pot::left_of_button;
+use crate::rng::Rng;
+use crate::table::{Table, Progress, Action, GameConfig, GameError, LegalActions};
+use crate::types::HandValue;
+
+// 52 - 8 * 5 = 12 cards are out of the hands besides the discards of the
+// seat drawing, so a draw always has enough cards
+pub const MAX_DRAW_PLAYERS: usize = 8;
+
+// A-5-4-3-2 as high cards (4 bits per number, see calc_hand())
+const WHEEL_HIGH: u32 = 0xc_3210;
+// order of the 5 high straight
+const WHEEL: u32 = 3;
+
+// 2-7 (Kansas City) lowball value of 5 cards, lower is better
+// Aces are always high and straights and flashes count, so this is
+// calc_hand() except that A-2-3-4-5 is not a straight.
+pub fn deuce_to_seven(hand: &[u32]) -> HandValue {
+    match calc_hand(hand) {
+        (Rank::Straight, WHEEL) => (Rank::HighCard, WHEEL_HIGH).into(),
+        (Rank::StraightFlash, WHEEL) => (Rank::Flash, WHEEL_HIGH).into(),
+        value => value.into(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DrawVariant {
+    // one draw, high hand
+    FiveCardDraw,
+    // three draws, 2-7 lowball
+    TripleDraw27,
+}
+
+impl DrawVariant {
+    pub fn draws(self) -> usize {
+        match self {
+            DrawVariant::FiveCardDraw => 1,
+            DrawVariant::TripleDraw27 => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DrawPhase {
+    // betting round n (0 = before the first draw)
+    Betting(usize),
+    // draw n (0 = the first draw)
+    Drawing(usize),
+    Showdown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrawRecord {
+    // 0 = the first draw
+    pub draw: usize,
+    pub seat: usize,
+    pub discarded: Vec<u32>,
+    pub drawn: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DrawGame<B = NoLimit> {
+    variant: DrawVariant,
+    config: GameConfig,
+    table: Table<B>,
+    // empty if the seat is not dealt in
+    hands: Vec<Vec<u32>>,
+    rng: Rng,
+    deck: Vec<u32>,
+    // discarded cards not yet shuffled into the deck
+    muck: Vec<u32>,
+    phase: DrawPhase,
+    to_draw: Option<usize>,
+    history: Vec<(DrawPhase, usize, Action)>,
+    draws: Vec<DrawRecord>,
+}
+
+impl DrawGame {
+    // no-limit
+    // stacks: chips of each seat (0 = empty seat), button: occupied seat
+    pub fn new(variant: DrawVariant, config: GameConfig, stacks: &[u64], button: usize, seed: u64) -> Self {
+        Self::with_structure(variant, config, NoLimit, stacks, button, seed)
+    }
+}
+
+impl<B: BettingStructure> DrawGame<B> {
+    pub fn with_structure(
+        variant: DrawVariant, config: GameConfig, structure: B,
+        stacks: &[u64], button: usize, seed: u64) -> Self
+    {
+        assert!(stacks.iter().filter(|&&s| s > 0).count() <= MAX_DRAW_PLAYERS);
+
+        let mut rng = Rng::new(seed);
+        let mut game = DrawGame {
+            variant,
+            config,
+            table: Table::new(structure, stacks, button),
+            hands: vec![Vec::new(); stacks.len()],
+            deck: rng.deck(&[]),
+            rng,
+            muck: Vec::new(),
+            phase: DrawPhase::Betting(0),
+            to_draw: None,
+            history: Vec::new(),
+            draws: Vec::new(),
+        };
+        // one card at a time, starting left of the button
+        let order = game.table.dealt_seats();
+        for _ in 0..HAND_SIZE {
+            for &i in order.iter() {
+                let card = game.deck.pop().unwrap();
+                game.hands[i].push(card);
+            }
+        }
+        if game.table.post_blinds(&config) == Progress::RoundOver {
+            game.end_betting();
+        }
+
+        game
+    }
+
+    pub fn variant(&self) -> DrawVariant {
+        self.variant
+    }
+
+    pub fn config(&self) -> GameConfig {
+        self.config
+    }
+
+    // chips, pots and forced bets
+    pub fn table(&self) -> &Table<B> {
+        &self.table
+    }
+
+    // empty if the seat is not dealt in
+    pub fn hand(&self, seat: usize) -> &[u32] {
+        &self.hands[seat]
+    }
+
+    pub fn phase(&self) -> DrawPhase {
+        self.phase
+    }
+
+    // the seat to bet (None while drawing)
+    pub fn to_act(&self) -> Option<usize> {
+        self.table.to_act()
+    }
+
+    // the seat to draw (None while betting)
+    pub fn to_draw(&self) -> Option<usize> {
+        self.to_draw
+    }
+
+    pub fn legal_actions(&self) -> LegalActions {
+        self.table.legal_actions()
+    }
+
+    // (phase, seat, action) in order, without forced bets
+    pub fn history(&self) -> &[(DrawPhase, usize, Action)] {
+        &self.history
+    }
+
+    pub fn draws(&self) -> &[DrawRecord] {
+        &self.draws
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.table.is_finished()
+    }
+
+    // chips won by each seat (all zero until finished)
+    pub fn payouts(&self) -> &[u64] {
+        self.table.payouts()
+    }
+
+    // see HoldemGame::post_missed_blinds()
+    pub fn post_missed_blinds(&mut self, seat: usize, small: bool, big: bool) -> Result<(), GameError> {
+        self.table.post_missed_blinds(&self.config, seat, small, big)
+    }
+
+    pub fn act(&mut self, action: Action) -> Result<(), GameError> {
+        if let DrawPhase::Drawing(_) = self.phase {
+            return Err(GameError::IllegalAction(action))
+        }
+
+        let seat = self.table.to_act().ok_or(GameError::Finished)?;
+        let progress = self.table.act(action)?;
+        self.history.push((self.phase, seat, action));
+        if progress == Progress::RoundOver {
+            self.end_betting();
+        }
+
+        Ok(())
+    }
+
+    // discard cards of the hand of to_draw() and replace them
+    // (standing pat = no discards) => the drawn cards
+    pub fn draw(&mut self, discards: &[u32]) -> Result<Vec<u32>, GameError> {
+        let (n, seat) = match (self.phase, self.to_draw) {
+            (DrawPhase::Drawing(n), Some(seat)) => (n, seat),
+            _ if self.is_finished() => return Err(GameError::Finished),
+            _ => return Err(GameError::InvalidDraw),
+        };
+        let hand = &self.hands[seat];
+        let valid = discards.iter().enumerate()
+            .all(|(k, c)| hand.contains(c) && !discards[..k].contains(c));
+        if !valid {
+            return Err(GameError::InvalidDraw)
+        }
+
+        self.hands[seat].retain(|c| !discards.contains(c));
+        let mut drawn = Vec::with_capacity(discards.len());
+        for _ in 0..discards.len() {
+            if self.deck.is_empty() {
+                self.deck.append(&mut self.muck);
+                self.rng.shuffle(&mut self.deck);
+            }
+            drawn.push(self.deck.pop().unwrap());
+        }
+        self.hands[seat].extend_from_slice(&drawn);
+        self.muck.extend_from_slice(discards);
+        self.draws.push(DrawRecord { draw: n, seat, discarded: discards.to_vec(), drawn: drawn.clone() });
+
+        let order = self.table.dealt_seats();
+        let pos = order.iter().position(|&i| i == seat).unwrap();
+        self.to_draw = order[pos + 1..].iter().copied().find(|&i| self.table.seats()[i].in_hand());
+        if self.to_draw.is_none() {
+            self.start_betting(n + 1);
+        }
+
+        Ok(drawn)
+    }
+
+    fn start_betting(&mut self, round: usize) {
+        self.phase = DrawPhase::Betting(round);
+        let big_bet = round >= self.variant.draws().div_ceil(2);
+        let first = (self.table.button() + 1) % self.hands.len();
+        // no betting when at most one player has chips
+        if !self.table.new_round(self.config.big_blind, big_bet)
+            || self.table.start_from(first) == Progress::RoundOver
+        {
+            self.end_betting();
+        }
+    }
+
+    fn end_betting(&mut self) {
+        let round = match self.phase {
+            DrawPhase::Betting(round) => round,
+            _ => unreachable!(),
+        };
+        if round == self.variant.draws() {
+            self.showdown();
+            return
+        }
+
+        self.phase = DrawPhase::Drawing(round);
+        let seats = self.table.seats();
+        self.to_draw = self.table.dealt_seats().into_iter().find(|&i| seats[i].in_hand());
+    }
+
+    // odd chips go to the first winner left of the button
+    fn showdown(&mut self) {
+        self.phase = DrawPhase::Showdown;
+        let odd_order = left_of_button(self.hands.len(), self.table.button());
+        let seats = self.table.seats();
+        let live = |i: usize| seats[i].in_hand();
+        match self.variant {
+            DrawVariant::FiveCardDraw => {
+                let values: Vec<Option<HandValue>> = (0..self.hands.len())
+                    .map(|i| live(i).then(|| calc_hand(&self.hands[i]).into()))
+                    .collect();
+                self.table.finish(&values, &odd_order);
+            }
+            DrawVariant::TripleDraw27 => {
+                let values: Vec<Option<Reverse<HandValue>>> = (0..self.hands.len())
+                    .map(|i| live(i).then(|| Reverse(deuce_to_seven(&self.hands[i]))))
+                    .collect();
+                self.table.finish(&values, &odd_order);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::betting::FixedLimit;
+    use crate::cards::list_from_text;
+
+    const CONFIG: GameConfig = GameConfig::new(1, 2);
+
+    fn value(text: &str) -> HandValue {
+        deuce_to_seven(&list_from_text(text).unwrap())
+    }
+
+    // check or call, then stand pat
+    fn passive<B: BettingStructure>(game: &mut DrawGame<B>) {
+        while !game.is_finished() {
+            if game.to_draw().is_some() {
+                game.draw(&[]).unwrap();
+            }
+            else {
+                let action = if game.legal_actions().check { Action::Check } else { Action::Call };
+                game.act(action).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn deuce_to_seven_order() {
+        let best = value("7c 5d 4h 3s 2c");
+        assert!(best < value("7c 6d 4h 3s 2c"));
+        assert!(best < value("8c 5d 4h 3s 2c"));
+        // the wheel is ace high, better than any pair
+        let wheel = value("Ac 5d 4h 3s 2c");
+        assert_eq!(wheel.rank, Rank::HighCard);
+        assert!(value("Kc Qd Jh 9s 8c") < wheel);
+        assert!(wheel < value("2d 2c 3h 4s 5c"));
+        // straights and flashes count
+        assert!(value("2d 2c 3h 4s 5c") < value("6c 5d 4h 3s 2c"));
+        assert!(value("9c 7c 5c 4c 2c") > value("Ac Kd Qh Js 9c"));
+        assert_eq!(value("Ac 5c 4c 3c 2c").rank, Rank::Flash);
+    }
+
+    #[test]
+    fn five_card_draw() {
+        let mut game = DrawGame::new(DrawVariant::FiveCardDraw, CONFIG, &[100, 100, 100], 0, 1);
+        assert!((0..3).all(|i| game.hand(i).len() == HAND_SIZE));
+        game.act(Action::Call).unwrap();
+        game.act(Action::Call).unwrap();
+        game.act(Action::Check).unwrap();
+        assert_eq!(game.phase(), DrawPhase::Drawing(0));
+        assert_eq!(game.to_act(), None);
+        assert_eq!(game.to_draw(), Some(1));
+        assert_eq!(game.act(Action::Check), Err(GameError::IllegalAction(Action::Check)));
+
+        let hand = game.hand(1).to_vec();
+        assert_eq!(game.draw(&[hand[0], hand[0]]), Err(GameError::InvalidDraw));
+        assert_eq!(game.draw(&[game.hand(2)[0]]), Err(GameError::InvalidDraw));
+        let drawn = game.draw(&hand[..2]).unwrap();
+        assert_eq!(drawn.len(), 2);
+        assert_eq!(game.hand(1).len(), HAND_SIZE);
+        assert!(!game.hand(1).contains(&hand[0]));
+        game.draw(&[]).unwrap();
+        let card = game.hand(0)[0];
+        game.draw(&[card]).unwrap();
+        assert_eq!(game.phase(), DrawPhase::Betting(1));
+        assert_eq!(game.to_act(), Some(1));
+
+        passive(&mut game);
+        assert_eq!(game.phase(), DrawPhase::Showdown);
+        assert_eq!(game.draws().len(), 3);
+        let best = (0..3).map(|i| calc_hand(game.hand(i))).max().unwrap();
+        for i in 0..3 {
+            assert_eq!(game.payouts()[i] > 0, calc_hand(game.hand(i)) == best);
+        }
+        assert_eq!(game.payouts().iter().sum::<u64>(), 6);
+    }
+
+    #[test]
+    fn reshuffle() {
+        // everyone draws five cards three times
+        let stacks = [100; MAX_DRAW_PLAYERS];
+        let mut game = DrawGame::new(DrawVariant::TripleDraw27, CONFIG, &stacks, 0, 3);
+        while !game.is_finished() {
+            if let Some(i) = game.to_draw() {
+                let hand = game.hand(i).to_vec();
+                game.draw(&hand).unwrap();
+            }
+            else {
+                let action = if game.legal_actions().check { Action::Check } else { Action::Call };
+                game.act(action).unwrap();
+            }
+        }
+        assert_eq!(game.draws().len(), 3 * MAX_DRAW_PLAYERS);
+        let mut cards: Vec<u32> = (0..MAX_DRAW_PLAYERS).flat_map(|i| game.hand(i).to_vec()).collect();
+        cards.extend_from_slice(&game.deck);
+        cards.extend_from_slice(&game.muck);
+        cards.sort_unstable();
+        assert_eq!(cards, (0..52).collect::<Vec<u32>>());
+
+        // the lowest hand wins
+        let best = (0..MAX_DRAW_PLAYERS).map(|i| deuce_to_seven(game.hand(i))).min().unwrap();
+        for i in 0..MAX_DRAW_PLAYERS {
+            assert_eq!(game.payouts()[i] > 0, deuce_to_seven(game.hand(i)) == best);
+        }
+    }
+
+    #[test]
+    fn triple_draw_limit() {
+        let limit = FixedLimit::new(2);
+        let mut game = DrawGame::with_structure(
+            DrawVariant::TripleDraw27, CONFIG, limit, &[100, 100], 0, 1);
+        let mut sizes = Vec::new();
+        while !game.is_finished() {
+            if game.to_draw().is_some() {
+                game.draw(&[]).unwrap();
+                continue
+            }
+            let legal = game.legal_actions();
+            match legal.bet {
+                Some((min, _)) => {
+                    sizes.push(min);
+                    game.act(Action::Bet(min)).unwrap();
+                }
+                None => game.act(if legal.check { Action::Check } else { Action::Call }).unwrap(),
+            }
+        }
+        // small bets before the second draw, big bets after
+        assert_eq!(sizes, vec![2, 4, 4]);
+        assert_eq!(game.table().pot(), 4 + 2 * (2 + 4 + 4));
+    }
+
+    #[test]
+    fn fold() {
+        let mut game = DrawGame::new(DrawVariant::FiveCardDraw, CONFIG, &[100, 100, 100], 0, 1);
+        game.act(Action::Fold).unwrap();
+        game.act(Action::Fold).unwrap();
+        assert!(game.is_finished());
+        assert_eq!(game.payouts(), &[0, 0, 3]);
+        assert_eq!(game.draw(&[]), Err(GameError::Finished));
+    }
+}
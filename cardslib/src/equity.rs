@@ -0,0 +1,180 @@
+// Hold'em equity calculation
+//
+// Equity is the expected share of the pot at showdown.
+// Ties are split between the winners.
+use alloc::{vec, vec::Vec};
+use crate::cards::{for_each_case, calc_best, Rank};
+use crate::rng::Rng;
+
+// hole cards of a hold'em player
+pub const HOLE_SIZE: usize = 2;
+// community cards at the river
+pub const BOARD_SIZE: usize = 5;
+
+fn assert_distinct(cards: &[u32]) {
+    let mut used = 0u64;
+    for &code in cards {
+        assert!(code < 64 && used & (1 << code) == 0, "duplicate card");
+        used |= 1 << code;
+    }
+}
+
+// best hand value of hole + board (5 cards)
+pub fn hand_value(hole: &[u32; HOLE_SIZE], board: &[u32]) -> (Rank, u32) {
+    assert!(board.len() == BOARD_SIZE);
+
+    let mut cards = [0u32; HOLE_SIZE + BOARD_SIZE];
+    cards[..HOLE_SIZE].copy_from_slice(hole);
+    cards[HOLE_SIZE..].copy_from_slice(board);
+
+    calc_best(&cards)
+}
+
+// add the pot share of a complete board into share
+fn showdown(hands: &[[u32; HOLE_SIZE]], board: &[u32], share: &mut [f64]) {
+    let mut values = [(Rank::HighCard, 0u32); 10];
+    let values = &mut values[..hands.len()];
+    for (value, hole) in values.iter_mut().zip(hands) {
+        *value = hand_value(hole, board);
+    }
+    let best = *values.iter().max().unwrap();
+    let winners = values.iter().filter(|&&v| v == best).count();
+    for (s, &v) in share.iter_mut().zip(values.iter()) {
+        if v == best {
+            *s += 1.0 / winners as f64;
+        }
+    }
+}
+
+// hands: hole cards of 2..=10 players, board: 0..=5 known cards
+// => equity of each player, enumerating all the rest boards
+pub fn equity(hands: &[[u32; HOLE_SIZE]], board: &[u32]) -> Vec<f64> {
+    assert!((2..=10).contains(&hands.len()));
+    assert!(board.len() <= BOARD_SIZE);
+
+    let mut fixed: Vec<u32> = hands.iter().flatten().copied().collect();
+    fixed.extend_from_slice(board);
+    assert_distinct(&fixed);
+
+    let mut share = vec![0.0; hands.len()];
+    let mut count = 0u64;
+    let mut full = [0u32; BOARD_SIZE];
+    full[..board.len()].copy_from_slice(board);
+    for_each_case(&fixed, BOARD_SIZE - board.len(), |rest| {
+        full[board.len()..].copy_from_slice(rest);
+        showdown(hands, &full, &mut share);
+        count += 1;
+    });
+
+    share.iter().map(|s| s / count as f64).collect()
+}
+
+// equity against opponents with random hole cards (Monte Carlo)
+pub fn equity_vs_random(
+    hole: &[u32; HOLE_SIZE], board: &[u32],
+    opponents: usize, trials: u32, seed: u64) -> f64
+{
+    assert!((1..=9).contains(&opponents));
+    assert!(board.len() <= BOARD_SIZE);
+    assert!(trials > 0);
+
+    let mut fixed = hole.to_vec();
+    fixed.extend_from_slice(board);
+    assert_distinct(&fixed);
+
+    let mut rng = Rng::new(seed);
+    let mut hands = vec![[0u32; HOLE_SIZE]; opponents + 1];
+    hands[0] = *hole;
+    let mut full = [0u32; BOARD_SIZE];
+    full[..board.len()].copy_from_slice(board);
+    let mut share = vec![0.0; opponents + 1];
+    for _ in 0..trials {
+        let mut deck = rng.deck(&fixed).into_iter();
+        for hand in hands[1..].iter_mut() {
+            for card in hand.iter_mut() {
+                *card = deck.next().unwrap();
+            }
+        }
+        for (card, next) in full[board.len()..].iter_mut().zip(deck) {
+            *card = next;
+        }
+        showdown(&hands, &full, &mut share);
+    }
+
+    share[0] / trials as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::encode;
+
+    #[test]
+    fn river() {
+        // AdKd vs QcQh on Qd 7d 2s 3h 9d (flash vs trips)
+        let hands = [
+            [encode(1, 12), encode(1, 11)],
+            [encode(0, 10), encode(2, 10)],
+        ];
+        let board = [encode(1, 10), encode(1, 5), encode(3, 0), encode(2, 1), encode(1, 7)];
+        assert_eq!(equity(&hands, &board), vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn split() {
+        // board plays: royal flash
+        let hands = [
+            [encode(0, 0), encode(1, 1)],
+            [encode(2, 2), encode(3, 3)],
+        ];
+        let board = [encode(1, 12), encode(1, 11), encode(1, 10), encode(1, 9), encode(1, 8)];
+        assert_eq!(equity(&hands, &board), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn turn() {
+        // AcAd vs KcKd on Ah Kh 2s 7s: only the last K wins for KK
+        let hands = [
+            [encode(0, 12), encode(1, 12)],
+            [encode(0, 11), encode(1, 11)],
+        ];
+        let board = [encode(2, 12), encode(2, 11), encode(3, 0), encode(3, 5)];
+        let result = equity(&hands, &board);
+        assert!((result[1] - 1.0 / 44.0).abs() < 1e-9);
+        assert!((result[0] + result[1] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn flop_three_way() {
+        let hands = [
+            [encode(0, 12), encode(1, 12)],
+            [encode(0, 11), encode(1, 11)],
+            [encode(2, 4), encode(2, 3)],
+        ];
+        let board = [encode(3, 8), encode(2, 2), encode(2, 1)];
+        let result = equity(&hands, &board);
+        assert!((result.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        // open ended straight flash draw is the favorite
+        assert!(result[2] > result[0]);
+    }
+
+    #[test]
+    fn vs_random() {
+        let aa = [encode(0, 12), encode(1, 12)];
+        let result = equity_vs_random(&aa, &[], 1, 20000, 1);
+        assert!(result > 0.83 && result < 0.87);
+        let result = equity_vs_random(&aa, &[], 4, 20000, 1);
+        assert!(result > 0.53 && result < 0.59);
+        // deterministic by seed
+        assert_eq!(
+            equity_vs_random(&aa, &[], 2, 100, 7),
+            equity_vs_random(&aa, &[], 2, 100, 7));
+    }
+
+    #[test]
+    #[should_panic]
+    fn duplicate() {
+        let hands = [[0, 1], [1, 2]];
+        equity(&hands, &[]);
+    }
+}
@@ -0,0 +1,305 @@
+// Hold'em hand histories
+//
+// A HandHistory records everything about a finished hand: seats, stacks,
+// forced bets, hole cards, actions, the board, the hands shown and how
+// each pot was awarded. With the "serde" feature it serializes to JSON
+// (cards as "As").
+//
+// replay() deals the same cards with a stacked deck, runs the actions
+// through the engine and checks that the results are the same. Hole cards
+// may be unknown (None) for hands which were not shown.
+use core::fmt;
+use alloc::{format, string::String, vec::Vec};
+use crate::betting::BettingStructure;
+use crate::equity::{HOLE_SIZE, BOARD_SIZE};
+use crate::holdem::{Action, GameConfig, GameError, HoldemGame, Post, Street, MAX_SEATS};
+use crate::pot::{left_of_button, PotBreakdown};
+use crate::types::{Card, HandValue};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandHistory {
+    pub id: u64,
+    pub config: GameConfig,
+    pub button: usize,
+    // name of each seat (empty for empty seats)
+    pub players: Vec<String>,
+    // chips at the start of the hand (0 = empty seat)
+    pub stacks: Vec<u64>,
+    pub holes: Vec<Option<[Card; HOLE_SIZE]>>,
+    pub posts: Vec<(usize, Post)>,
+    pub actions: Vec<(Street, usize, Action)>,
+    pub board: Vec<Card>,
+    // hands at the showdown, left of the button first
+    pub showdown: Vec<(usize, HandValue)>,
+    pub pots: PotBreakdown,
+    // chips won or lost by each seat
+    pub net: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    // actions[index] was rejected by the engine
+    Action { index: usize, error: GameError },
+    // the engine does not agree with this part of the history
+    Mismatch(&'static str),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Action { index, error } => write!(f, "action {}: {}", index, error),
+            ReplayError::Mismatch(what) => write!(f, "{} differ", what),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReplayError {}
+
+fn cards_of(codes: &[u32]) -> Vec<Card> {
+    codes.iter().map(|&c| Card::new(c)).collect()
+}
+
+impl HandHistory {
+    // game: a finished hand, players: names of each seat
+    pub fn from_game<B: BettingStructure>(game: &HoldemGame<B>, id: u64, players: Vec<String>) -> Self {
+        assert!(game.is_finished());
+        assert_eq!(players.len(), game.seats().len());
+
+        let net = game.net_results();
+        let n = game.seats().len();
+        let showdown = if game.street() == Street::Showdown {
+            left_of_button(n, game.button()).into_iter()
+                .filter(|&i| game.seats()[i].in_hand())
+                .map(|i| {
+                    let mut codes = game.hole(i).unwrap().to_vec();
+                    codes.extend_from_slice(game.board());
+                    (i, HandValue::of(&codes))
+                })
+                .collect()
+        }
+        else {
+            Vec::new()
+        };
+
+        HandHistory {
+            id,
+            config: game.config(),
+            button: game.button(),
+            players,
+            stacks: game.seats().iter().zip(&net).map(|(s, &d)| (s.stack as i64 - d) as u64).collect(),
+            holes: (0..n).map(|i| game.hole(i).map(|h| [Card::new(h[0]), Card::new(h[1])])).collect(),
+            posts: game.posts().to_vec(),
+            actions: game.history().to_vec(),
+            board: cards_of(game.board()),
+            showdown,
+            pots: game.pot_breakdown().clone(),
+            net,
+        }
+    }
+
+    // the cards in the order dealt, unknown hole cards are any unused cards
+    fn deck(&self) -> Vec<u32> {
+        let mut known: Vec<u32> = self.holes.iter().flatten().flatten().map(|c| c.code()).collect();
+        known.extend(self.board.iter().map(|c| c.code()));
+        let mut unused = (0..crate::cards::CARDS_NUM).filter(|c| !known.contains(c));
+
+        let order: Vec<usize> = left_of_button(self.stacks.len(), self.button).into_iter()
+            .filter(|&i| self.stacks[i] > 0)
+            .collect();
+        let mut cards = Vec::with_capacity(order.len() * HOLE_SIZE + self.board.len());
+        for k in 0..HOLE_SIZE {
+            for &i in order.iter() {
+                let card = match self.holes[i] {
+                    Some(hole) => hole[k].code(),
+                    None => unused.next().unwrap(),
+                };
+                cards.push(card);
+            }
+        }
+        cards.extend(self.board.iter().map(|c| c.code()));
+
+        cards
+    }
+
+    // runs the hand again => the finished game if everything matches
+    pub fn replay<B: BettingStructure>(&self, structure: B) -> Result<HoldemGame<B>, ReplayError> {
+        let n = self.stacks.len();
+        let players = self.stacks.iter().filter(|&&s| s > 0).count();
+        if !(2..=MAX_SEATS).contains(&n) || players < 2 || self.button >= n || self.stacks[self.button] == 0
+            || self.players.len() != n || self.holes.len() != n || self.net.len() != n
+        {
+            return Err(ReplayError::Mismatch("seats"))
+        }
+        let mut used = 0u64;
+        let known = self.holes.iter().flatten().flatten().chain(self.board.iter());
+        for card in known {
+            if used >> card.code() & 1 == 1 {
+                return Err(ReplayError::Mismatch("cards"))
+            }
+            used |= 1 << card.code();
+        }
+        if self.board.len() > BOARD_SIZE {
+            return Err(ReplayError::Mismatch("board"))
+        }
+        if !self.config.is_valid() {
+            return Err(ReplayError::Mismatch("config"))
+        }
+        let mut game = HoldemGame::with_deck(self.config, structure, &self.stacks, self.button, &self.deck());
+
+        // blinds of returning seats follow the regular ones
+        let regular = game.posts().len();
+        if !self.posts.starts_with(game.posts()) {
+            return Err(ReplayError::Mismatch("posts"))
+        }
+        let mut missed: Vec<(usize, bool, bool)> = Vec::new();
+        for &(seat, post) in self.posts[regular..].iter() {
+            let k = match missed.iter().position(|m| m.0 == seat) {
+                Some(k) => k,
+                None => {
+                    missed.push((seat, false, false));
+                    missed.len() - 1
+                }
+            };
+            match post {
+                Post::DeadSmallBlind(_) => missed[k].1 = true,
+                Post::BigBlind(_) => missed[k].2 = true,
+                _ => return Err(ReplayError::Mismatch("posts")),
+            }
+        }
+        for (seat, small, big) in missed {
+            game.post_missed_blinds(seat, small, big).map_err(|_| ReplayError::Mismatch("posts"))?;
+        }
+        if game.posts() != self.posts.as_slice() {
+            return Err(ReplayError::Mismatch("posts"))
+        }
+
+        for (index, &(street, seat, action)) in self.actions.iter().enumerate() {
+            if game.street() != street || game.to_act() != Some(seat) {
+                return Err(ReplayError::Mismatch("actions"))
+            }
+            game.act(action).map_err(|error| ReplayError::Action { index, error })?;
+        }
+        if !game.is_finished() {
+            return Err(ReplayError::Mismatch("actions"))
+        }
+
+        let replayed = Self::from_game(&game, self.id, self.players.clone());
+        if replayed.board != self.board {
+            return Err(ReplayError::Mismatch("board"))
+        }
+        // unknown hands can not be shown
+        if replayed.showdown != self.showdown {
+            return Err(ReplayError::Mismatch("showdown"))
+        }
+        if replayed.pots != self.pots {
+            return Err(ReplayError::Mismatch("pots"))
+        }
+        if replayed.net != self.net {
+            return Err(ReplayError::Mismatch("results"))
+        }
+
+        Ok(game)
+    }
+}
+
+// default names: "Seat 1", "Seat 2", ...
+pub fn seat_names(n: usize) -> Vec<String> {
+    (1..=n).map(|i| format!("Seat {}", i)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::betting::NoLimit;
+    use crate::player::{play, RandomPlayer};
+
+    fn random_hand(seed: u64) -> HandHistory {
+        let mut game = HoldemGame::new(GameConfig::new(1, 2), &[100, 60, 0, 150], 0, seed);
+        let mut players: Vec<RandomPlayer> = (0..4).map(|i| RandomPlayer::new(seed * 4 + i)).collect();
+        play(&mut game, &mut players).unwrap();
+        HandHistory::from_game(&game, seed, seat_names(4))
+    }
+
+    #[test]
+    fn record() {
+        let history = random_hand(1);
+        assert_eq!(history.stacks, vec![100, 60, 0, 150]);
+        assert_eq!(history.holes[2], None);
+        assert_eq!(history.posts[0], (1, Post::SmallBlind(1)));
+        assert_eq!(history.net.iter().sum::<i64>(), 0);
+        let pots: u64 = history.pots.pots.iter().map(|a| a.pot.amount).sum();
+        assert_eq!(history.pots.payouts.iter().sum::<u64>(), pots);
+    }
+
+    #[test]
+    fn replay() {
+        for seed in 0..50 {
+            let history = random_hand(seed);
+            let game = history.replay(NoLimit).unwrap();
+            assert_eq!(HandHistory::from_game(&game, seed, seat_names(4)), history);
+
+            // folded hands do not matter
+            let mut unknown = history.clone();
+            for (i, hole) in unknown.holes.iter_mut().enumerate() {
+                if !history.showdown.iter().any(|s| s.0 == i) {
+                    *hole = None;
+                }
+            }
+            assert!(unknown.replay(NoLimit).is_ok());
+        }
+    }
+
+    #[test]
+    fn replay_errors() {
+        let mut history = random_hand(3);
+        history.net[0] += 1;
+        assert_eq!(history.replay(NoLimit).unwrap_err(), ReplayError::Mismatch("results"));
+
+        let mut history = random_hand(3);
+        history.actions.insert(0, (Street::Preflop, 0, Action::Raise(1000)));
+        assert!(matches!(history.replay(NoLimit), Err(ReplayError::Action { index: 0, .. })));
+
+        let mut history = random_hand(3);
+        history.actions.truncate(history.actions.len() - 1);
+        assert_eq!(history.replay(NoLimit).unwrap_err(), ReplayError::Mismatch("actions"));
+
+        let mut history = random_hand(3);
+        history.holes[0] = history.holes[1];
+        assert_eq!(history.replay(NoLimit).unwrap_err(), ReplayError::Mismatch("cards"));
+        history.button = 2;
+        assert_eq!(history.replay(NoLimit).unwrap_err(), ReplayError::Mismatch("seats"));
+
+        // e.g. from JSON
+        let mut history = random_hand(3);
+        history.config.big_blind = 0;
+        assert_eq!(history.replay(NoLimit).unwrap_err(), ReplayError::Mismatch("config"));
+        let mut history = random_hand(3);
+        history.config.straddle = history.config.big_blind;
+        assert_eq!(history.replay(NoLimit).unwrap_err(), ReplayError::Mismatch("config"));
+    }
+
+    #[test]
+    fn dead_blinds() {
+        let mut game = HoldemGame::new(GameConfig::new(1, 2), &[100, 100, 100, 100], 0, 9);
+        game.post_missed_blinds(3, true, true).unwrap();
+        while !game.is_finished() {
+            let action = if game.legal_actions().check { Action::Check } else { Action::Call };
+            game.act(action).unwrap();
+        }
+        let history = HandHistory::from_game(&game, 9, seat_names(4));
+        assert_eq!(history.replay(NoLimit).unwrap().posts(), game.posts());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json() {
+        let history = random_hand(5);
+        let json = serde_json::to_string(&history).unwrap();
+        assert!(json.contains(r#""config":{"small_blind":1,"big_blind":2,"ante":"None","straddle":0}"#));
+        let parsed: HandHistory = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, history);
+        assert!(parsed.replay(NoLimit).is_ok());
+    }
+}
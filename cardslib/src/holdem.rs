@@ -0,0 +1,553 @@
+// Texas Hold'em game state machine
+//
+// Pure and deterministic: no I/O, and the deck is shuffled by Rng with
+// the given seed, so the same seed and actions always give the same hand.
+// Drive a hand with to_act(), legal_actions() and act() until
+// is_finished(), then read payouts().
+//
+// Chips and betting rounds are kept by table::Table. Bet sizes follow the
+// BettingStructure (no-limit by default, see HoldemGame::with_structure()).
+// Antes and the straddle are set in GameConfig, blinds missed by a
+// returning seat are posted with post_missed_blinds().
+use alloc::{vec, vec::Vec};
+use crate::betting::{BettingStructure, NoLimit};
+use crate::cards::CARDS_NUM;
+use crate::equity::{hand_value, HOLE_SIZE, BOARD_SIZE};
+use crate::player::PlayerView;
+use crate::pot::{left_of_button, PotBreakdown};
+use crate::rng::Rng;
+use crate::table::{Table, Progress};
+use crate::types::HandValue;
+pub use crate::table::{Action, Ante, GameConfig, GameError, LegalActions, Post, Seat, MAX_SEATS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+    Showdown,
+}
+
+#[derive(Debug, Clone)]
+pub struct HoldemGame<B = NoLimit> {
+    config: GameConfig,
+    table: Table<B>,
+    holes: Vec<Option<[u32; HOLE_SIZE]>>,
+    deck: Vec<u32>,
+    board: Vec<u32>,
+    street: Street,
+    history: Vec<(Street, usize, Action)>,
+}
+
+impl HoldemGame {
+    // no-limit hold'em
+    // stacks: chips of each seat (0 = empty seat), button: occupied seat
+    // Blinds are posted and hole cards are dealt.
+    pub fn new(config: GameConfig, stacks: &[u64], button: usize, seed: u64) -> Self {
+        Self::with_structure(config, NoLimit, stacks, button, seed)
+    }
+}
+
+impl<B: BettingStructure> HoldemGame<B> {
+    pub fn with_structure(
+        config: GameConfig, structure: B, stacks: &[u64], button: usize, seed: u64) -> Self
+    {
+        Self::start(config, structure, stacks, button, Rng::new(seed).deck(&[]))
+    }
+
+    // a stacked deck (replaying a hand history)
+    // cards: dealt first in this order (hole cards one at a time from left
+    // of the button, then the board), the other cards follow
+    pub fn with_deck(
+        config: GameConfig, structure: B, stacks: &[u64], button: usize, cards: &[u32]) -> Self
+    {
+        let mut deck = cards.to_vec();
+        deck.extend((0..CARDS_NUM).filter(|c| !cards.contains(c)));
+        assert!(deck.len() == CARDS_NUM as usize, "duplicate card");
+        // dealt from the end
+        deck.reverse();
+
+        Self::start(config, structure, stacks, button, deck)
+    }
+
+    fn start(config: GameConfig, structure: B, stacks: &[u64], button: usize, deck: Vec<u32>) -> Self {
+        let mut game = HoldemGame {
+            config,
+            table: Table::new(structure, stacks, button),
+            holes: vec![None; stacks.len()],
+            deck,
+            board: Vec::with_capacity(BOARD_SIZE),
+            street: Street::Preflop,
+            history: Vec::new(),
+        };
+        game.deal_holes();
+        if game.table.post_blinds(&config) == Progress::RoundOver {
+            game.next_street();
+        }
+
+        game
+    }
+
+    pub fn config(&self) -> GameConfig {
+        self.config
+    }
+
+    // chips, pots and forced bets
+    pub fn table(&self) -> &Table<B> {
+        &self.table
+    }
+
+    pub fn structure(&self) -> &B {
+        self.table.structure()
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        self.table.seats()
+    }
+
+    // None if the seat is not dealt in
+    pub fn hole(&self, seat: usize) -> Option<[u32; HOLE_SIZE]> {
+        self.holes[seat]
+    }
+
+    pub fn button(&self) -> usize {
+        self.table.button()
+    }
+
+    pub fn board(&self) -> &[u32] {
+        &self.board
+    }
+
+    pub fn street(&self) -> Street {
+        self.street
+    }
+
+    // None when the hand is finished
+    pub fn to_act(&self) -> Option<usize> {
+        self.table.to_act()
+    }
+
+    pub fn current_bet(&self) -> u64 {
+        self.table.current_bet()
+    }
+
+    // all chips put in the hand
+    pub fn pot(&self) -> u64 {
+        self.table.pot()
+    }
+
+    // (seat, forced bet) in order of posting
+    pub fn posts(&self) -> &[(usize, Post)] {
+        self.table.posts()
+    }
+
+    // (street, seat, action) in order, without forced bets
+    pub fn history(&self) -> &[(Street, usize, Action)] {
+        &self.history
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.table.is_finished()
+    }
+
+    // chips won by each seat (all zero until finished)
+    pub fn payouts(&self) -> &[u64] {
+        self.table.payouts()
+    }
+
+    // main and side pots and who won them (empty until finished)
+    pub fn pot_breakdown(&self) -> &PotBreakdown {
+        self.table.pot_breakdown()
+    }
+
+    // payout - committed of each seat
+    pub fn net_results(&self) -> Vec<i64> {
+        self.table.net_results()
+    }
+
+    pub fn legal_actions(&self) -> LegalActions {
+        self.table.legal_actions()
+    }
+
+    // what the seat may see (None if not dealt in)
+    pub fn view(&self, seat: usize) -> Option<PlayerView<'_>> {
+        let legal = if self.to_act() == Some(seat) { self.legal_actions() } else { LegalActions::default() };
+        Some(PlayerView {
+            seat,
+            hole: self.holes[seat]?,
+            board: &self.board,
+            street: self.street,
+            button: self.button(),
+            seats: self.seats(),
+            pot: self.pot(),
+            current_bet: self.current_bet(),
+            legal,
+            history: &self.history,
+        })
+    }
+
+    fn deal_card(&mut self) -> u32 {
+        self.deck.pop().unwrap()
+    }
+
+    // one card at a time, starting left of the button
+    fn deal_holes(&mut self) {
+        let order = self.table.dealt_seats();
+        let first: Vec<u32> = order.iter().map(|_| self.deal_card()).collect();
+        for (&i, card) in order.iter().zip(first) {
+            let second = self.deal_card();
+            self.holes[i] = Some([card, second]);
+        }
+    }
+
+    // a seat returning after missing blinds, before the first action
+    // The big blind is live (the seat gets the option), the small blind dead.
+    pub fn post_missed_blinds(&mut self, seat: usize, small: bool, big: bool) -> Result<(), GameError> {
+        self.table.post_missed_blinds(&self.config, seat, small, big)
+    }
+
+    pub fn act(&mut self, action: Action) -> Result<(), GameError> {
+        let seat = self.table.to_act().ok_or(GameError::Finished)?;
+        let progress = self.table.act(action)?;
+        self.history.push((self.street, seat, action));
+        if progress == Progress::RoundOver {
+            self.next_street();
+        }
+
+        Ok(())
+    }
+
+    fn next_street(&mut self) {
+        loop {
+            if self.street == Street::River {
+                self.showdown();
+                return
+            }
+
+            self.street = match self.street {
+                Street::Preflop => Street::Flop,
+                Street::Flop => Street::Turn,
+                _ => Street::River,
+            };
+            let count = if self.street == Street::Flop { 3 } else { 1 };
+            for _ in 0..count {
+                let card = self.deal_card();
+                self.board.push(card);
+            }
+
+            // no betting when at most one player has chips
+            let first = (self.table.button() + 1) % self.holes.len();
+            if self.table.new_round(self.config.big_blind, self.street >= Street::Turn)
+                && self.table.start_from(first) == Progress::Next
+            {
+                return
+            }
+        }
+    }
+
+    // odd chips go to the first winner left of the button
+    fn showdown(&mut self) {
+        self.street = Street::Showdown;
+        let values: Vec<Option<HandValue>> = self.holes.iter().zip(self.table.seats())
+            .map(|(hole, seat)| match hole {
+                Some(hole) if seat.in_hand() => Some(hand_value(hole, &self.board).into()),
+                _ => None,
+            })
+            .collect();
+        let odd_order = left_of_button(self.holes.len(), self.table.button());
+        self.table.finish(&values, &odd_order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::betting::{PotLimit, FixedLimit};
+
+    const CONFIG: GameConfig = GameConfig::new(1, 2);
+
+    fn total(game: &HoldemGame) -> u64 {
+        game.seats().iter().map(|s| s.stack).sum::<u64>() + game.pot()
+            - game.payouts().iter().sum::<u64>()
+    }
+
+    #[test]
+    fn blinds_and_order() {
+        let game = HoldemGame::new(CONFIG, &[100, 100, 100, 100], 0, 1);
+        assert_eq!(game.seats()[1].bet, 1);
+        assert_eq!(game.seats()[2].bet, 2);
+        assert_eq!(game.to_act(), Some(3));
+        assert_eq!(game.pot(), 3);
+        let legal = game.legal_actions();
+        assert!(legal.fold && !legal.check);
+        assert_eq!(legal.call, Some(2));
+        assert_eq!(legal.raise, Some((4, 100)));
+        assert_eq!(legal.bet, None);
+
+        // all hole cards and the deck are distinct
+        let mut cards: Vec<u32> = (0..4).flat_map(|i| game.hole(i).unwrap()).collect();
+        cards.extend_from_slice(&game.deck);
+        cards.sort_unstable();
+        assert_eq!(cards, (0..52).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn heads_up() {
+        // the button posts the small blind, acts first preflop and last after
+        let mut game = HoldemGame::new(CONFIG, &[100, 0, 100], 2, 1);
+        assert_eq!(game.seats()[2].bet, 1);
+        assert_eq!(game.seats()[0].bet, 2);
+        assert!(game.hole(1).is_none());
+        assert_eq!(game.to_act(), Some(2));
+        game.act(Action::Call).unwrap();
+        // the big blind can still raise
+        assert_eq!(game.to_act(), Some(0));
+        assert!(game.legal_actions().check);
+        game.act(Action::Check).unwrap();
+        assert_eq!(game.street(), Street::Flop);
+        assert_eq!(game.board().len(), 3);
+        assert_eq!(game.to_act(), Some(0));
+    }
+
+    #[test]
+    fn fold_to_big_blind() {
+        let mut game = HoldemGame::new(CONFIG, &[100, 100, 100], 0, 1);
+        game.act(Action::Fold).unwrap();
+        game.act(Action::Fold).unwrap();
+        assert!(game.is_finished());
+        assert_eq!(game.street(), Street::Preflop);
+        assert_eq!(game.payouts(), &[0, 0, 3]);
+        assert_eq!(game.net_results(), vec![0, -1, 1]);
+        assert_eq!(game.seats()[2].stack, 101);
+        assert_eq!(game.act(Action::Check), Err(GameError::Finished));
+    }
+
+    #[test]
+    fn min_raise() {
+        let mut game = HoldemGame::new(CONFIG, &[100, 100, 100], 0, 1);
+        assert_eq!(game.act(Action::Raise(3)), Err(GameError::IllegalAction(Action::Raise(3))));
+        assert_eq!(game.act(Action::Bet(4)), Err(GameError::IllegalAction(Action::Bet(4))));
+        game.act(Action::Raise(7)).unwrap();
+        // the last raise was 5
+        assert_eq!(game.legal_actions().raise, Some((12, 100)));
+        game.act(Action::Raise(12)).unwrap();
+        game.act(Action::Call).unwrap();
+        game.act(Action::Call).unwrap();
+        assert_eq!(game.street(), Street::Flop);
+        assert_eq!(game.pot(), 36);
+        // postflop: the first seat left of the button, bet from the big blind
+        assert_eq!(game.to_act(), Some(1));
+        assert_eq!(game.legal_actions().bet, Some((2, 88)));
+    }
+
+    #[test]
+    fn check_down() {
+        let mut game = HoldemGame::new(CONFIG, &[100, 100, 100], 0, 7);
+        while let Some(i) = game.to_act() {
+            let action = if game.legal_actions().check { Action::Check } else { Action::Call };
+            assert!(game.seats()[i].in_hand());
+            game.act(action).unwrap();
+        }
+        assert_eq!(game.street(), Street::Showdown);
+        assert_eq!(game.board().len(), 5);
+        assert_eq!(game.history().len(), 3 + 3 * 3);
+        assert_eq!(game.payouts().iter().sum::<u64>(), 6);
+        assert_eq!(total(&game), 300);
+
+        let value = |i: usize| -> HandValue {
+            hand_value(&game.hole(i).unwrap(), game.board()).into()
+        };
+        let best = (0..3).map(value).max().unwrap();
+        for i in 0..3 {
+            assert_eq!(game.payouts()[i] > 0, value(i) == best);
+        }
+    }
+
+    #[test]
+    fn side_pots() {
+        let mut game = HoldemGame::new(CONFIG, &[50, 100, 200, 200], 0, 3);
+        game.act(Action::AllIn).unwrap();
+        game.act(Action::AllIn).unwrap();
+        game.act(Action::AllIn).unwrap();
+        game.act(Action::Call).unwrap();
+        assert!(game.is_finished());
+        assert_eq!(game.street(), Street::Showdown);
+        assert_eq!(game.board().len(), 5);
+        assert_eq!(game.pot(), 550);
+        assert_eq!(game.payouts().iter().sum::<u64>(), 550);
+        assert_eq!(total(&game), 550);
+        // the 50 stack can win at most the main pot
+        assert!(game.payouts()[0] <= 200);
+        let pots = &game.pot_breakdown().pots;
+        assert_eq!(pots.len(), 3);
+        assert_eq!(pots[0].pot.amount, 200);
+        assert_eq!(pots[1].pot.eligible, vec![1, 2, 3]);
+        assert_eq!(pots[2].pot.eligible, vec![2, 3]);
+    }
+
+    #[test]
+    fn short_all_in_call() {
+        let mut game = HoldemGame::new(CONFIG, &[100, 100, 30], 0, 5);
+        game.act(Action::Raise(50)).unwrap();
+        game.act(Action::Fold).unwrap();
+        // the big blind can not cover the raise: call or all-in only
+        let legal = game.legal_actions();
+        assert_eq!(legal.call, Some(28));
+        assert_eq!(legal.raise, None);
+        assert_eq!(legal.all_in, Some(30));
+        game.act(Action::Call).unwrap();
+        assert!(game.is_finished());
+        // 20 uncalled chips come back to the raiser
+        assert!(game.payouts()[0] >= 20);
+        let last = game.pot_breakdown().pots.last().unwrap();
+        assert!(last.pot.uncalled && last.pot.amount == 20 && last.winners == vec![0]);
+        assert_eq!(game.payouts().iter().sum::<u64>(), 81);
+    }
+
+    #[test]
+    fn incomplete_raise() {
+        let mut game = HoldemGame::new(CONFIG, &[14, 100, 100, 100], 0, 1);
+        game.act(Action::Raise(10)).unwrap();
+        // short of the min raise to 18
+        assert_eq!(game.legal_actions().raise, Some((14, 14)));
+        game.act(Action::AllIn).unwrap();
+        game.act(Action::Fold).unwrap();
+        // the big blind has not acted yet
+        assert_eq!(game.legal_actions().raise, Some((22, 100)));
+        game.act(Action::Call).unwrap();
+        // the raiser faces 4 more, which does not reopen the action
+        let legal = game.legal_actions();
+        assert_eq!(game.to_act(), Some(3));
+        assert_eq!(legal.call, Some(4));
+        assert_eq!(legal.raise, None);
+        assert_eq!(legal.all_in, None);
+        game.act(Action::Call).unwrap();
+        assert_eq!(game.street(), Street::Flop);
+    }
+
+    #[test]
+    fn pot_limit() {
+        let mut game = HoldemGame::with_structure(CONFIG, PotLimit, &[100, 100, 100], 0, 1);
+        assert_eq!(game.legal_actions().raise, Some((4, 7)));
+        assert_eq!(game.legal_actions().all_in, None);
+        assert!(game.act(Action::Raise(8)).is_err());
+        game.act(Action::Raise(7)).unwrap();
+        // pot 10, call 6 => raise 16 more
+        assert_eq!(game.legal_actions().raise, Some((12, 23)));
+    }
+
+    #[test]
+    fn fixed_limit() {
+        let limit = FixedLimit::new(2);
+        let mut game = HoldemGame::with_structure(CONFIG, limit, &[100, 100, 100], 0, 1);
+        assert_eq!(game.legal_actions().raise, Some((4, 4)));
+        game.act(Action::Raise(4)).unwrap();
+        game.act(Action::Raise(6)).unwrap();
+        game.act(Action::Raise(8)).unwrap();
+        // capped at 4 bets
+        assert_eq!(game.legal_actions().raise, None);
+        game.act(Action::Call).unwrap();
+        game.act(Action::Call).unwrap();
+        assert_eq!(game.street(), Street::Flop);
+        assert_eq!(game.legal_actions().bet, Some((2, 2)));
+        for _ in 0..3 {
+            game.act(Action::Check).unwrap();
+        }
+        assert_eq!(game.street(), Street::Turn);
+        assert_eq!(game.legal_actions().bet, Some((4, 4)));
+    }
+
+    #[test]
+    fn antes() {
+        let config = GameConfig { ante: Ante::Each(1), ..CONFIG };
+        let game = HoldemGame::new(config, &[100, 100, 0, 100, 100], 0, 1);
+        assert_eq!(game.pot(), 7);
+        assert_eq!(game.seats()[1].bet, 1);
+        assert_eq!(game.seats()[1].committed, 2);
+        assert_eq!(game.current_bet(), 2);
+        assert_eq!(game.posts(), &[
+            (0, Post::Ante(1)), (1, Post::Ante(1)), (3, Post::Ante(1)), (4, Post::Ante(1)),
+            (1, Post::SmallBlind(1)), (3, Post::BigBlind(2)),
+        ]);
+
+        // the blind comes first when the big blind is short
+        let config = GameConfig { ante: Ante::BigBlind(3), ..CONFIG };
+        let game = HoldemGame::new(config, &[100, 100, 4], 0, 1);
+        assert_eq!(game.seats()[2].bet, 2);
+        assert_eq!(game.seats()[2].committed, 4);
+        assert_eq!(game.posts()[2], (2, Post::Ante(2)));
+    }
+
+    #[test]
+    fn ante_all_in() {
+        let config = GameConfig { ante: Ante::Each(2), ..CONFIG };
+        let mut game = HoldemGame::new(config, &[2, 100, 100], 0, 1);
+        assert!(game.seats()[0].is_all_in());
+        assert_eq!(game.to_act(), Some(1));
+        game.act(Action::Call).unwrap();
+        game.act(Action::Check).unwrap();
+        while game.to_act().is_some() {
+            game.act(Action::Check).unwrap();
+        }
+        let pots = &game.pot_breakdown().pots;
+        assert_eq!(pots[0].pot.amount, 6);
+        assert_eq!(pots[0].pot.eligible, vec![0, 1, 2]);
+        assert_eq!(pots[1].pot.amount, 4);
+        assert_eq!(game.payouts().iter().sum::<u64>(), 10);
+    }
+
+    #[test]
+    fn straddle() {
+        let config = GameConfig { straddle: 4, ..CONFIG };
+        let mut game = HoldemGame::new(config, &[100, 100, 100, 100], 0, 1);
+        assert_eq!(game.posts()[2], (3, Post::Straddle(4)));
+        assert_eq!(game.current_bet(), 4);
+        // the button acts first, raises are by at least the straddle
+        assert_eq!(game.to_act(), Some(0));
+        assert_eq!(game.legal_actions().call, Some(4));
+        assert_eq!(game.legal_actions().raise, Some((8, 100)));
+        game.act(Action::Call).unwrap();
+        game.act(Action::Call).unwrap();
+        game.act(Action::Call).unwrap();
+        // the straddle has the option
+        assert_eq!(game.to_act(), Some(3));
+        assert!(game.legal_actions().check);
+        game.act(Action::Check).unwrap();
+        assert_eq!(game.street(), Street::Flop);
+        assert_eq!(game.pot(), 16);
+
+        // no straddle heads-up
+        let game = HoldemGame::new(config, &[100, 100], 0, 1);
+        assert_eq!(game.current_bet(), 2);
+    }
+
+    #[test]
+    fn dead_blinds() {
+        let mut game = HoldemGame::new(CONFIG, &[100, 100, 100, 100, 100], 0, 1);
+        assert_eq!(game.post_missed_blinds(1, true, true), Err(GameError::InvalidPost(1)));
+        game.post_missed_blinds(4, true, true).unwrap();
+        assert_eq!(game.seats()[4].bet, 2);
+        assert_eq!(game.seats()[4].committed, 3);
+        assert_eq!(game.pot(), 6);
+        assert_eq!(game.post_missed_blinds(4, false, true), Err(GameError::InvalidPost(4)));
+
+        game.act(Action::Call).unwrap();
+        // the live big blind can check
+        assert_eq!(game.to_act(), Some(4));
+        assert!(game.legal_actions().check);
+        game.act(Action::Check).unwrap();
+        assert_eq!(game.post_missed_blinds(0, false, true), Err(GameError::InvalidPost(0)));
+    }
+
+    #[test]
+    fn deterministic() {
+        let game1 = HoldemGame::new(CONFIG, &[100, 100, 100], 0, 42);
+        let game2 = HoldemGame::new(CONFIG, &[100, 100, 100], 0, 42);
+        let game3 = HoldemGame::new(CONFIG, &[100, 100, 100], 0, 43);
+        let holes = |game: &HoldemGame| (0..3).map(|i| game.hole(i)).collect::<Vec<_>>();
+        assert_eq!(holes(&game1), holes(&game2));
+        assert_ne!(holes(&game1), holes(&game3));
+    }
+}
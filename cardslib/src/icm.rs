@@ -0,0 +1,242 @@
+// Independent Chip Model: tournament equity of stacks
+//
+// The chance to finish first is the share of the chips (Malmuth-Harville),
+// then the same among the rest for second place, and so on.
+// stacks: chips of each player (0 = already out, no prize)
+// payouts: prize of 1st, 2nd, ... place
+//
+// icm_exact() recurses over the subsets of the players left (up to
+// MAX_EXACT players), icm_monte_carlo() samples finishing orders for
+// large fields. icm() picks one.
+use alloc::{vec, vec::Vec};
+use crate::equity::HOLE_SIZE;
+use crate::preflop::{class_combos, Range, CLASS_NUM};
+use crate::rng::Rng;
+
+pub const MAX_EXACT: usize = 16;
+// finishing orders sampled by icm() for large fields
+pub const DEFAULT_TRIALS: u32 = 100_000;
+
+// => prize equity of each player
+pub fn icm_exact(stacks: &[u64], payouts: &[f64]) -> Vec<f64> {
+    let alive: Vec<usize> = (0..stacks.len()).filter(|&i| stacks[i] > 0).collect();
+    let n = alive.len();
+    assert!(n <= MAX_EXACT);
+
+    let mut result = vec![0.0; stacks.len()];
+    let full = (1usize << n) - 1;
+    // [mask]: probability that the players in mask are the ones left
+    let mut prob = vec![0.0; full + 1];
+    prob[full] = 1.0;
+    // a mask comes after all of its supersets in descending order
+    for mask in (1..=full).rev() {
+        let p = prob[mask];
+        let place = n - mask.count_ones() as usize;
+        if p == 0.0 || place >= payouts.len() {
+            continue
+        }
+        let total: u64 = (0..n).filter(|k| mask >> k & 1 == 1).map(|k| stacks[alive[k]]).sum();
+        for k in (0..n).filter(|k| mask >> k & 1 == 1) {
+            let q = p * stacks[alive[k]] as f64 / total as f64;
+            result[alive[k]] += q * payouts[place];
+            prob[mask & !(1 << k)] += q;
+        }
+    }
+
+    result
+}
+
+// trials: sampled finishing orders (only the paid places are sampled)
+pub fn icm_monte_carlo(stacks: &[u64], payouts: &[f64], trials: u32, seed: u64) -> Vec<f64> {
+    assert!(trials > 0);
+
+    let mut rng = Rng::new(seed);
+    let mut result = vec![0.0; stacks.len()];
+    let alive: Vec<usize> = (0..stacks.len()).filter(|&i| stacks[i] > 0).collect();
+    let places = payouts.len().min(alive.len());
+    let all: u64 = alive.iter().map(|&i| stacks[i]).sum();
+    for _ in 0..trials {
+        let mut left = alive.clone();
+        let mut total = all;
+        for &prize in payouts[..places].iter() {
+            // the winner of this place in proportion to the chips
+            let mut pick = rng.below(total);
+            let k = left.iter().position(|&i| {
+                if pick < stacks[i] {
+                    return true
+                }
+                pick -= stacks[i];
+                false
+            }).unwrap();
+            let i = left.swap_remove(k);
+            result[i] += prize;
+            total -= stacks[i];
+        }
+    }
+
+    for r in result.iter_mut() {
+        *r /= trials as f64;
+    }
+    result
+}
+
+// exact for up to MAX_EXACT players left, otherwise Monte Carlo
+pub fn icm(stacks: &[u64], payouts: &[f64]) -> Vec<f64> {
+    if stacks.iter().filter(|&&s| s > 0).count() <= MAX_EXACT {
+        icm_exact(stacks, payouts)
+    }
+    else {
+        icm_monte_carlo(stacks, payouts, DEFAULT_TRIALS, 0)
+    }
+}
+
+// hero goes all-in, only villain can call (everyone else has folded)
+// The pot (blinds and antes) goes to villain if hero folds instead,
+// e.g. hero in the small blind and villain in the big blind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShoveSpot<'a> {
+    // chips behind of each player, after posting
+    pub stacks: &'a [u64],
+    pub payouts: &'a [f64],
+    pub pot: u64,
+    pub hero: usize,
+    pub villain: usize,
+}
+
+// results of hero, the evs are shove - fold
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShoveEv {
+    pub fold: f64,
+    pub shove: f64,
+    // chips
+    pub chip_ev: f64,
+    // prize equity
+    pub icm_ev: f64,
+}
+
+impl ShoveSpot<'_> {
+    // stacks after hero won or lost the pot with the chips at risk
+    fn stacks_after(&self, hero_wins: bool, risk: u64) -> Vec<u64> {
+        let mut stacks = self.stacks.to_vec();
+        let (winner, loser) = if hero_wins { (self.hero, self.villain) } else { (self.villain, self.hero) };
+        stacks[winner] += self.pot + risk;
+        stacks[loser] -= risk;
+        stacks
+    }
+
+    // call: probability villain calls, equity: of hero when called
+    pub fn evaluate(&self, call: f64, equity: f64) -> ShoveEv {
+        assert!(self.hero != self.villain);
+
+        let risk = self.stacks[self.hero].min(self.stacks[self.villain]);
+        let hero = self.stacks[self.hero] as f64;
+        let prize = |stacks: &[u64]| icm(stacks, self.payouts)[self.hero];
+
+        let folded = self.stacks_after(false, 0);
+        let stolen = self.stacks_after(true, 0);
+        let won = self.stacks_after(true, risk);
+        let lost = self.stacks_after(false, risk);
+
+        let chips_called = equity * won[self.hero] as f64 + (1.0 - equity) * lost[self.hero] as f64;
+        let chips = (1.0 - call) * stolen[self.hero] as f64 + call * chips_called;
+        let fold = prize(&folded);
+        let shove = (1.0 - call) * prize(&stolen)
+            + call * (equity * prize(&won) + (1.0 - equity) * prize(&lost));
+
+        ShoveEv { fold, shove, chip_ev: chips - hero, icm_ev: shove - fold }
+    }
+
+    // villain calls with the range, equity(hero, villain) of hole cards
+    // (e.g. PreflopTable::equity_hands())
+    pub fn evaluate_range<F>(&self, hole: &[u32; HOLE_SIZE], range: &Range, equity: F) -> ShoveEv
+        where F: Fn(&[u32; HOLE_SIZE], &[u32; HOLE_SIZE]) -> f64
+    {
+        let (mut combos, mut calls, mut sum) = (0u32, 0u32, 0.0);
+        for class in 0..CLASS_NUM {
+            for villain in class_combos(class) {
+                if villain.iter().any(|c| hole.contains(c)) {
+                    continue
+                }
+                combos += 1;
+                if range.contains(class) {
+                    calls += 1;
+                    sum += equity(hole, &villain);
+                }
+            }
+        }
+        let equity = if calls > 0 { sum / calls as f64 } else { 0.0 };
+
+        self.evaluate(calls as f64 / combos as f64, equity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::list_from_text;
+
+    fn close(a: &[f64], b: &[f64], eps: f64) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < eps)
+    }
+
+    #[test]
+    fn exact() {
+        // 1st: 0.5, 2nd: P(i second) = sum over j first of s_j/T * s_i/(T - s_j)
+        let result = icm_exact(&[50, 30, 20], &[50.0, 30.0, 20.0]);
+        let second = |i: f64, a: f64, b: f64| a / 100.0 * i / (100.0 - a) + b / 100.0 * i / (100.0 - b);
+        let p1 = 0.5 * 50.0 + second(50.0, 30.0, 20.0) * 30.0;
+        assert!((result[0] - (p1 + (1.0 - 0.5 - second(50.0, 30.0, 20.0)) * 20.0)).abs() < 1e-9);
+        assert!((result.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+
+        // equal stacks split equally, winner-take-all is the chip share
+        assert!(close(&icm_exact(&[10, 10, 10, 10], &[60.0, 40.0]), &[25.0; 4], 1e-9));
+        assert!(close(&icm_exact(&[30, 10], &[100.0]), &[75.0, 25.0], 1e-9));
+        // busted players get nothing, the places shift
+        assert!(close(&icm_exact(&[30, 0, 10], &[70.0, 30.0]), &[60.0, 0.0, 40.0], 1e-9));
+    }
+
+    #[test]
+    fn monte_carlo() {
+        let stacks = [5000, 3000, 2500, 1200, 800, 500];
+        let payouts = [50.0, 30.0, 20.0];
+        let exact = icm_exact(&stacks, &payouts);
+        let approx = icm_monte_carlo(&stacks, &payouts, 50_000, 1);
+        assert!(close(&exact, &approx, 0.5));
+
+        // a large field
+        let stacks: Vec<u64> = (1..=40).map(|i| i * 100).collect();
+        let result = icm(&stacks, &payouts);
+        assert!((result.iter().sum::<f64>() - 100.0).abs() < 1e-6);
+        assert!(result.windows(2).all(|w| w[0] <= w[1] + 0.2));
+    }
+
+    #[test]
+    fn shove() {
+        // winner-take-all: $EV is proportional to chip EV
+        let spot = ShoveSpot { stacks: &[1000, 1000], payouts: &[100.0], pot: 100, hero: 0, villain: 1 };
+        let ev = spot.evaluate(0.5, 0.4);
+        assert!((ev.chip_ev - (0.5 * 100.0 + 0.5 * (0.4 * 1100.0 - 0.6 * 1000.0))).abs() < 1e-9);
+        assert!((ev.icm_ev - ev.chip_ev * 100.0 / 2100.0).abs() < 1e-9);
+        assert!((ev.fold - 100.0 * 1000.0 / 2100.0).abs() < 1e-9);
+
+        // on the bubble a chip-winning shove can lose money
+        let stacks = [2000, 2000, 2000, 300];
+        let spot = ShoveSpot { stacks: &stacks, payouts: &[50.0, 30.0, 20.0], pot: 150, hero: 0, villain: 1 };
+        let ev = spot.evaluate(1.0, 0.5);
+        assert!(ev.chip_ev > 0.0);
+        assert!(ev.icm_ev < 0.0);
+    }
+
+    #[test]
+    fn shove_range() {
+        let cards = list_from_text("As Ah").unwrap();
+        let hole = [cards[0], cards[1]];
+        let spot = ShoveSpot { stacks: &[1000, 1000], payouts: &[100.0], pot: 100, hero: 0, villain: 1 };
+        // villain only calls with kings: 6 of 1225 combos
+        let range: Range = "KK".parse().unwrap();
+        let ev = spot.evaluate_range(&hole, &range, |_, _| 0.8);
+        assert_eq!(ev, spot.evaluate(6.0 / 1225.0, 0.8));
+        let ev = spot.evaluate_range(&hole, &Range::new(), |_, _| 0.8);
+        assert_eq!(ev, spot.evaluate(0.0, 0.0));
+    }
+}
@@ -0,0 +1,533 @@
+// Suit isomorphism and perfect hand indexing
+//
+// Suits have no order in hold'em, so hands which differ only by a suit
+// permutation are strategically the same (e.g. 1326 preflop combos => 169).
+// HandIndexer maps a set of cards dealt over several rounds
+// (e.g. [2, 3, 1, 1] = hole, flop, turn, river) to a dense integer
+// in 0..size(round) and back.
+//
+// Each suit is described by its rank masks per round (a "suit config").
+// Canonical form sorts suits by (card count vector, suit config index)
+// in descending order and renames them to 0, 1, 2, 3.
+// The index is computed as:
+//   offset of the count vector configuration
+//   + mixed radix of multiset indices of suit configs sharing a count vector
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+use crate::cards::{encode, decode, choose, SUIT_NUM, NUMBER_NUM, CARDS_NUM};
+
+const SUITS: usize = SUIT_NUM as usize;
+
+// number of multisets of size k from n elements
+fn multichoose(n: u64, k: u64) -> u64 {
+    if k == 0 {
+        return 1
+    }
+    choose(n + k - 1, k)
+}
+
+// values: descending order, each < n
+fn multiset_index(values: &[u64]) -> u64 {
+    // ascending x[i] => strictly ascending x[i] + i
+    values.iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &x)| choose(x + i as u64, i as u64 + 1))
+        .sum()
+}
+
+// inverse of multiset_index, returns descending order
+fn multiset_unindex(n: u64, k: usize, mut index: u64) -> Vec<u64> {
+    let mut result = Vec::with_capacity(k);
+    for i in (1..=k as u64).rev() {
+        // find the largest y where C(y, i) <= index
+        let mut lo = i - 1;
+        let mut hi = n + i - 1;
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if choose(mid, i) <= index {
+                lo = mid;
+            }
+            else {
+                hi = mid;
+            }
+        }
+        index -= choose(lo, i);
+        result.push(lo - (i - 1));
+    }
+
+    result
+}
+
+// colex index of a bit set among n bits
+fn colex_index(mask: u32) -> u64 {
+    let mut value = 0;
+    let mut i = 0;
+    for pos in 0..NUMBER_NUM {
+        if mask & (1 << pos) != 0 {
+            i += 1;
+            value += choose(pos as u64, i);
+        }
+    }
+
+    value
+}
+
+// inverse of colex_index (bit count = k)
+fn colex_unindex(k: u32, mut index: u64) -> u32 {
+    let mut mask = 0u32;
+    for i in (1..=k as u64).rev() {
+        let mut pos = i - 1;
+        while choose(pos + 1, i) <= index {
+            pos += 1;
+        }
+        index -= choose(pos, i);
+        mask |= 1 << pos;
+    }
+
+    mask
+}
+
+// remove bits in used from mask and pack the remaining positions
+fn compress(mask: u32, used: u32) -> u32 {
+    let mut result = 0u32;
+    let mut dst = 0;
+    for pos in 0..NUMBER_NUM {
+        if used & (1 << pos) != 0 {
+            continue
+        }
+        if mask & (1 << pos) != 0 {
+            result |= 1 << dst;
+        }
+        dst += 1;
+    }
+
+    result
+}
+
+// inverse of compress
+fn expand(packed: u32, used: u32) -> u32 {
+    let mut result = 0u32;
+    let mut src = 0;
+    for pos in 0..NUMBER_NUM {
+        if used & (1 << pos) != 0 {
+            continue
+        }
+        if packed & (1 << src) != 0 {
+            result |= 1 << pos;
+        }
+        src += 1;
+    }
+
+    result
+}
+
+// number of suit configs for a count vector
+fn suit_config_size(counts: &[u8]) -> u64 {
+    let mut rest = NUMBER_NUM as u64;
+    let mut size = 1;
+    for &c in counts {
+        size *= choose(rest, c as u64);
+        rest -= c as u64;
+    }
+
+    size
+}
+
+// per round masks => dense index (mixed radix, round 0 is the least significant)
+fn suit_config_index(masks: &[u32]) -> u64 {
+    let mut used = 0u32;
+    let mut rest = NUMBER_NUM as u64;
+    let mut radix = 1;
+    let mut value = 0;
+    for &mask in masks {
+        let c = mask.count_ones() as u64;
+        value += radix * colex_index(compress(mask, used));
+        radix *= choose(rest, c);
+        rest -= c;
+        used |= mask;
+    }
+
+    value
+}
+
+fn suit_config_unindex(counts: &[u8], mut index: u64) -> Vec<u32> {
+    let mut used = 0u32;
+    let mut rest = NUMBER_NUM as u64;
+    let mut masks = Vec::with_capacity(counts.len());
+    for &c in counts {
+        let size = choose(rest, c as u64);
+        let mask = expand(colex_unindex(c as u32, index % size), used);
+        index /= size;
+        rest -= c as u64;
+        used |= mask;
+        masks.push(mask);
+    }
+
+    masks
+}
+
+// count vectors of 4 suits (descending order) for a round
+type Config = Vec<Vec<u8>>;
+
+struct RoundTable {
+    // configs in index order
+    configs: Vec<Config>,
+    // first index of each config
+    offsets: Vec<u64>,
+    lookup: BTreeMap<Config, usize>,
+    size: u64,
+}
+
+impl RoundTable {
+    fn new(cards_per_round: &[usize]) -> Self {
+        let mut configs = Vec::new();
+        let mut state = Vec::new();
+        let mut rest: Vec<u8> = cards_per_round.iter().map(|&c| c as u8).collect();
+        enum_configs(&mut state, &mut rest, &mut configs);
+
+        let mut offsets = Vec::with_capacity(configs.len());
+        let mut lookup = BTreeMap::new();
+        let mut size = 0;
+        for (i, config) in configs.iter().enumerate() {
+            offsets.push(size);
+            lookup.insert(config.clone(), i);
+            size += config_size(config);
+        }
+
+        Self { configs, offsets, lookup, size }
+    }
+}
+
+fn enum_configs(state: &mut Config, rest: &mut Vec<u8>, result: &mut Vec<Config>) {
+    if state.len() >= SUITS {
+        if rest.iter().all(|&c| c == 0) {
+            result.push(state.clone());
+        }
+        return
+    }
+
+    // each round can have at most NUMBER_NUM cards of a suit
+    let mut counts = vec![0u8; rest.len()];
+    loop {
+        let total: u32 = counts.iter().map(|&c| c as u32).sum();
+        let descending = state.last().is_none_or(|last| counts <= *last);
+        if total <= NUMBER_NUM && descending {
+            for (r, &c) in rest.iter_mut().zip(counts.iter()) {
+                *r -= c;
+            }
+            state.push(counts.clone());
+            enum_configs(state, rest, result);
+            state.pop();
+            for (r, &c) in rest.iter_mut().zip(counts.iter()) {
+                *r += c;
+            }
+        }
+
+        // next counts vector (odometer, counts[i] <= rest[i])
+        let mut i = 0;
+        while i < counts.len() {
+            if counts[i] < rest[i] {
+                counts[i] += 1;
+                break
+            }
+            counts[i] = 0;
+            i += 1;
+        }
+        if i >= counts.len() {
+            break
+        }
+    }
+}
+
+// groups of equal count vectors: (start, len)
+fn groups(config: &[Vec<u8>]) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    for i in 1..=config.len() {
+        if i == config.len() || config[i] != config[start] {
+            result.push((start, i - start));
+            start = i;
+        }
+    }
+
+    result
+}
+
+fn config_size(config: &[Vec<u8>]) -> u64 {
+    groups(config).iter()
+        .map(|&(start, len)| multichoose(suit_config_size(&config[start]), len as u64))
+        .product()
+}
+
+pub struct HandIndexer {
+    cards_per_round: Vec<usize>,
+    tables: Vec<RoundTable>,
+}
+
+impl HandIndexer {
+    // cards_per_round: e.g. [2, 3, 1, 1] for hold'em
+    pub fn new(cards_per_round: &[usize]) -> Self {
+        assert!(!cards_per_round.is_empty());
+        assert!(cards_per_round.iter().sum::<usize>() <= CARDS_NUM as usize);
+
+        let tables = (1..=cards_per_round.len())
+            .map(|r| RoundTable::new(&cards_per_round[..r]))
+            .collect();
+
+        Self { cards_per_round: cards_per_round.to_vec(), tables }
+    }
+
+    // hole, flop, turn, river
+    pub fn holdem() -> Self {
+        Self::new(&[2, 3, 1, 1])
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.cards_per_round.len()
+    }
+
+    // total cards dealt until the end of round
+    pub fn cards_until(&self, round: usize) -> usize {
+        self.cards_per_round[..=round].iter().sum()
+    }
+
+    // number of canonical hands at round
+    pub fn size(&self, round: usize) -> u64 {
+        self.tables[round].size
+    }
+
+    // cards: cards of round 0, 1, ..., round (len determines the round)
+    // => (round, per suit masks)
+    fn suit_masks(&self, cards: &[u32]) -> (usize, Vec<[u32; SUITS]>) {
+        let round = (0..self.rounds())
+            .find(|&r| self.cards_until(r) == cards.len())
+            .expect("invalid cards count");
+
+        let mut all = 0u64;
+        let mut masks = vec![[0u32; SUITS]; round + 1];
+        let mut pos = 0;
+        for (r, round_masks) in masks.iter_mut().enumerate() {
+            for &code in &cards[pos..pos + self.cards_per_round[r]] {
+                assert!(all & (1 << code) == 0, "duplicate card");
+                all |= 1 << code;
+                let (s, n) = decode(code);
+                round_masks[s as usize] |= 1 << n;
+            }
+            pos += self.cards_per_round[r];
+        }
+
+        (round, masks)
+    }
+
+    // suits in canonical order: [(counts, suit config index, original suit)]
+    fn sorted_suits(&self, cards: &[u32]) -> (usize, Vec<(Vec<u8>, u64, u32)>) {
+        let (round, masks) = self.suit_masks(cards);
+
+        let mut suits: Vec<(Vec<u8>, u64, u32)> = (0..SUITS)
+            .map(|s| {
+                let suit_masks: Vec<u32> = masks.iter().map(|m| m[s]).collect();
+                let counts = suit_masks.iter().map(|m| m.count_ones() as u8).collect();
+                (counts, suit_config_index(&suit_masks), s as u32)
+            })
+            .collect();
+        // descending by (counts, index)
+        suits.sort_by(|a, b| (&b.0, b.1).cmp(&(&a.0, a.1)));
+
+        (round, suits)
+    }
+
+    // original suit => canonical suit
+    pub fn canonical_suits(&self, cards: &[u32]) -> [u32; SUITS] {
+        let (_, suits) = self.sorted_suits(cards);
+
+        let mut perm = [0u32; SUITS];
+        for (i, (_, _, s)) in suits.iter().enumerate() {
+            perm[*s as usize] = i as u32;
+        }
+
+        perm
+    }
+
+    // apply the canonical suit permutation
+    // cards of each round are sorted in ascending order
+    pub fn canonicalize(&self, cards: &[u32]) -> Vec<u32> {
+        let perm = self.canonical_suits(cards);
+
+        let mut result = Vec::with_capacity(cards.len());
+        let mut pos = 0;
+        for &count in &self.cards_per_round {
+            if pos >= cards.len() {
+                break
+            }
+            let mut round: Vec<u32> = cards[pos..pos + count].iter()
+                .map(|&code| {
+                    let (s, n) = decode(code);
+                    encode(perm[s as usize], n)
+                })
+                .collect();
+            round.sort_unstable();
+            result.extend(round);
+            pos += count;
+        }
+
+        result
+    }
+
+    // cards of round 0..=r => 0..size(r)
+    pub fn index(&self, cards: &[u32]) -> u64 {
+        let (round, suits) = self.sorted_suits(cards);
+        let table = &self.tables[round];
+
+        let config: Config = suits.iter().map(|(counts, _, _)| counts.clone()).collect();
+        let id = table.lookup[&config];
+
+        let mut value = 0;
+        for (start, len) in groups(&config) {
+            let n = suit_config_size(&config[start]);
+            let values: Vec<u64> = suits[start..start + len].iter()
+                .map(|&(_, idx, _)| idx)
+                .collect();
+            value = value * multichoose(n, len as u64) + multiset_index(&values);
+        }
+
+        table.offsets[id] + value
+    }
+
+    // inverse of index, returns canonical cards (same as canonicalize)
+    pub fn unindex(&self, round: usize, index: u64) -> Vec<u32> {
+        let table = &self.tables[round];
+        assert!(index < table.size);
+
+        let id = match table.offsets.binary_search(&index) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        let config = &table.configs[id];
+        let mut rest = index - table.offsets[id];
+
+        // decode mixed radix from the last group
+        let mut suit_indices = [0u64; SUITS];
+        for (start, len) in groups(config).into_iter().rev() {
+            let n = suit_config_size(&config[start]);
+            let size = multichoose(n, len as u64);
+            let values = multiset_unindex(n, len, rest % size);
+            rest /= size;
+            suit_indices[start..start + len].copy_from_slice(&values);
+        }
+
+        let mut rounds = vec![Vec::new(); round + 1];
+        for (suit, counts) in config.iter().enumerate() {
+            let masks = suit_config_unindex(counts, suit_indices[suit]);
+            for (r, &mask) in masks.iter().enumerate() {
+                for n in 0..NUMBER_NUM {
+                    if mask & (1 << n) != 0 {
+                        rounds[r].push(encode(suit as u32, n));
+                    }
+                }
+            }
+        }
+
+        rounds.into_iter()
+            .flat_map(|mut cards| {
+                cards.sort_unstable();
+                cards
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards;
+
+    #[test]
+    fn sizes() {
+        let indexer = HandIndexer::holdem();
+        assert_eq!(indexer.size(0), 169);
+        assert_eq!(indexer.size(1), 1_286_792);
+        assert_eq!(indexer.size(2), 55_190_538);
+        assert_eq!(indexer.size(3), 2_428_287_420);
+    }
+
+    #[test]
+    fn preflop_all() {
+        let indexer = HandIndexer::holdem();
+        let mut count = vec![0; 169];
+        for hand in cards::all_case(&[], 2) {
+            let index = indexer.index(&hand);
+            count[index as usize] += 1;
+            let canon = indexer.unindex(0, index);
+            assert_eq!(canon, indexer.canonicalize(&hand));
+            assert_eq!(indexer.index(&canon), index);
+        }
+        // pairs: 6, suited: 4, offsuit: 12
+        assert_eq!(count.iter().filter(|&&c| c == 6).count(), 13);
+        assert_eq!(count.iter().filter(|&&c| c == 4).count(), 78);
+        assert_eq!(count.iter().filter(|&&c| c == 12).count(), 78);
+    }
+
+    #[test]
+    fn flop_roundtrip() {
+        let indexer = HandIndexer::holdem();
+        let size = indexer.size(1);
+        for index in (0..size).step_by(997) {
+            let canon = indexer.unindex(1, index);
+            assert_eq!(canon.len(), 5);
+            assert_eq!(indexer.index(&canon), index);
+            assert_eq!(indexer.canonicalize(&canon), canon);
+        }
+    }
+
+    #[test]
+    fn river_roundtrip() {
+        let indexer = HandIndexer::holdem();
+        let size = indexer.size(3);
+        for index in (0..size).step_by(1_000_003) {
+            let canon = indexer.unindex(3, index);
+            assert_eq!(indexer.index(&canon), index);
+        }
+    }
+
+    #[test]
+    fn isomorphic() {
+        let indexer = HandIndexer::holdem();
+        // AcKc 2c3d4h vs AdKd 2d3s4c
+        let hand1 = [
+            cards::encode(0, 12), cards::encode(0, 11),
+            cards::encode(0, 0), cards::encode(1, 1), cards::encode(2, 2),
+        ];
+        let hand2 = [
+            cards::encode(1, 12), cards::encode(1, 11),
+            cards::encode(1, 0), cards::encode(3, 1), cards::encode(0, 2),
+        ];
+        // board order in a round does not matter
+        let hand3 = [
+            cards::encode(1, 11), cards::encode(1, 12),
+            cards::encode(0, 2), cards::encode(1, 0), cards::encode(3, 1),
+        ];
+        // hole and board are distinguished
+        let hand4 = [
+            cards::encode(0, 12), cards::encode(0, 0),
+            cards::encode(0, 11), cards::encode(1, 1), cards::encode(2, 2),
+        ];
+        assert_eq!(indexer.index(&hand1), indexer.index(&hand2));
+        assert_eq!(indexer.index(&hand1), indexer.index(&hand3));
+        assert_ne!(indexer.index(&hand1), indexer.index(&hand4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_invalid_count() {
+        let indexer = HandIndexer::holdem();
+        indexer.index(&[0, 1, 2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_duplicate() {
+        let indexer = HandIndexer::holdem();
+        indexer.index(&[0, 0]);
+    }
+}
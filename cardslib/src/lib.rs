@@ -1,5 +1,53 @@
+// no_std compatible (tests need the default features)
+//   std (default): file I/O of the preflop table, std::error::Error, sim
+//   alloc: functions and modules which return Vec or String
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod betting;
+#[cfg(feature = "alloc")]
+pub mod cfr;
+#[cfg(feature = "alloc")]
+pub mod draw;
+#[cfg(feature = "alloc")]
+pub mod index;
+#[cfg(feature = "alloc")]
+pub mod equity;
+#[cfg(feature = "alloc")]
+pub mod history;
+#[cfg(feature = "alloc")]
+pub mod holdem;
+#[cfg(feature = "alloc")]
+pub mod icm;
+pub mod odds;
+#[cfg(feature = "alloc")]
+pub mod player;
+#[cfg(feature = "alloc")]
+pub mod pokerstars;
+#[cfg(feature = "alloc")]
+pub mod pot;
+#[cfg(feature = "alloc")]
+pub mod preflop;
+#[cfg(feature = "alloc")]
+pub mod pushfold;
+pub mod rng;
+#[cfg(feature = "std")]
+pub mod sim;
+#[cfg(feature = "alloc")]
+pub mod stats;
+#[cfg(feature = "alloc")]
+pub mod stud;
+#[cfg(feature = "alloc")]
+pub mod table;
+pub mod types;
+
 #[allow(dead_code)]
-mod cards {
+pub mod cards {
+    #[cfg(feature = "alloc")]
+    use alloc::{string::String, vec::Vec};
+
     // A hand is 5-cards-set
     pub const HAND_SIZE: usize = 5;
     // 4 suits
@@ -15,7 +63,17 @@ mod cards {
     // 52 cards
     pub const CARDS_NUM: u32 = SUIT_NUM * NUMBER_NUM;
 
-    #[derive(Debug, PartialEq, Eq)]
+    // number => char (2, 3, ..., T, J, Q, K, A)
+    pub const NUMBER_CHARS: [char; NUMBER_NUM as usize] = [
+        '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+    ];
+
+    // suit => char (c, d, h, s)
+    pub const SUIT_CHARS: [char; SUIT_NUM as usize] = ['c', 'd', 'h', 's'];
+
+    // weaker rank first, so Rank can be compared with < and >
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum Rank {
         HighCard,
         OnePair,
@@ -28,6 +86,20 @@ mod cards {
         StraightFlash,
     }
 
+    pub const RANK_NUM: usize = 9;
+    // weaker first (same as Rank as usize)
+    pub const RANKS: [Rank; RANK_NUM] = [
+        Rank::HighCard,
+        Rank::OnePair,
+        Rank::TwoPair,
+        Rank::Trips,
+        Rank::Straight,
+        Rank::Flash,
+        Rank::FullHouse,
+        Rank::Quads,
+        Rank::StraightFlash,
+    ];
+
     // (suit, number) => u32
     pub fn encode(suit: u32, number: u32) -> u32 {
         assert!(suit < SUIT_NUM && number < NUMBER_NUM);
@@ -42,6 +114,62 @@ mod cards {
         (code / NUMBER_NUM, code % NUMBER_NUM)
     }
 
+    // order of single cards: by number, then by suit
+    // Suits rank c < d < h < s (bridge order, the same as suit codes),
+    // as used by stud to decide the bring-in.
+    pub fn card_order(code: u32) -> u32 {
+        let (suit, number) = decode(code);
+
+        number * SUIT_NUM + suit
+    }
+
+    // u32 => "As"
+    #[cfg(feature = "alloc")]
+    pub fn to_text(code: u32) -> String {
+        let (s, n) = decode(code);
+
+        [NUMBER_CHARS[n as usize], SUIT_CHARS[s as usize]].iter().collect()
+    }
+
+    // "As" => u32 (number and suit are case insensitive)
+    pub fn from_text(text: &str) -> Option<u32> {
+        let mut chars = text.chars();
+        let (nc, sc) = (chars.next()?, chars.next()?);
+        if chars.next().is_some() {
+            return None
+        }
+        let n = NUMBER_CHARS.iter().position(|&c| c == nc.to_ascii_uppercase())?;
+        let s = SUIT_CHARS.iter().position(|&c| c == sc.to_ascii_lowercase())?;
+
+        Some(encode(s as u32, n as u32))
+    }
+
+    // [u32] => "As Kh Qd"
+    #[cfg(feature = "alloc")]
+    pub fn list_to_text(codes: &[u32]) -> String {
+        let list: Vec<String> = codes.iter().map(|&code| to_text(code)).collect();
+
+        list.join(" ")
+    }
+
+    // "AsKhQd", "As Kh Qd" or "As,Kh,Qd" => [u32]
+    // Err(position of the invalid card)
+    #[cfg(feature = "alloc")]
+    pub fn list_from_text(text: &str) -> Result<Vec<u32>, usize> {
+        let chars: Vec<(usize, char)> = text.char_indices()
+            .filter(|(_, c)| !c.is_whitespace() && *c != ',')
+            .collect();
+
+        chars.chunks(2)
+            .map(|pair| {
+                let pos = pair[0].0;
+                let card: String = pair.iter().map(|(_, c)| c).collect();
+                from_text(&card).ok_or(pos)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "alloc")]
     pub fn all_case(fixed_cards: &[u32], size: usize) -> Vec<Vec<u32>>
     {
         assert!(fixed_cards.len() <= size);
@@ -58,6 +186,42 @@ mod cards {
         result
     }
 
+    // same cases as all_case(), but calls f for each case instead of collecting
+    // fixed_cards can be more than size (e.g. hole cards + board)
+    pub fn for_each_case<F>(fixed_cards: &[u32], size: usize, mut f: F)
+        where F: FnMut(&[u32])
+    {
+        assert!(size <= CARDS_NUM as usize);
+
+        let mut used = [false; CARDS_NUM as usize];
+        let mut state = [0u32; CARDS_NUM as usize];
+
+        for &num in fixed_cards {
+            used[num as usize] = true;
+        }
+        for_each_case_rec(&mut state, 0, size, 0, &used, &mut f);
+    }
+
+    fn for_each_case_rec<F>(
+        state: &mut [u32], len: usize, size: usize, start: u32,
+        used: &[bool], f: &mut F)
+        where F: FnMut(&[u32])
+    {
+        if len >= size {
+            f(&state[..len]);
+            return
+        }
+
+        for num in start..CARDS_NUM {
+            if used[num as usize] {
+                continue
+            }
+            state[len] = num;
+            for_each_case_rec(state, len + 1, size, num + 1, used, f);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
     fn all_case_rec(
         state: &mut Vec<u32>, size: usize, start: u32,
         used: &[bool], result: &mut Vec<Vec<u32>>)
@@ -101,7 +265,7 @@ mod cards {
         let straight = find_straight(&nl_sorted);
 
         if let Some(order) = straight {
-            if let Some(_) = flash {
+            if flash.is_some() {
                 // use straight order
                 return (Rank::StraightFlash, order)
             }
@@ -131,6 +295,127 @@ mod cards {
         (Rank::HighCard, find_highcard(&nl_sorted))
     }
 
+    // [u32; 5..=7] => the best (Rank, order) of 5 cards in it
+    // Same result as max of calc_hand() for all 5-cards subsets,
+    // but uses bit masks instead of enumerating subsets.
+    pub fn calc_best(cards: &[u32]) -> (Rank, u32) {
+        assert!(cards.len() >= HAND_SIZE && cards.len() <= 7);
+
+        // suit => number mask
+        let mut sm = [0u32; SUIT_NUM as usize];
+        let mut nb = [0i32; NUMBER_NUM as usize];
+        for &code in cards {
+            let (s, n) = decode(code);
+            sm[s as usize] |= 1 << n;
+            nb[n as usize] += 1;
+        }
+        let all = sm.iter().fold(0, |acc, &m| acc | m);
+
+        let flash = sm.iter().find(|m| m.count_ones() >= HAND_SIZE as u32);
+        if let Some(&mask) = flash {
+            if let Some(order) = find_straight_mask(mask) {
+                return (Rank::StraightFlash, order)
+            }
+        }
+        // numbers with count >= c (descending order)
+        let nums = |c: i32| (0..NUMBER_NUM).rev().filter(move |&n| nb[n as usize] >= c);
+        // heads + the highest numbers except for heads
+        let with_kickers = |heads: &[u32], size: usize| -> u32 {
+            let mut list = [0u32; HAND_SIZE];
+            list[..heads.len()].copy_from_slice(heads);
+            let mut len = heads.len();
+            for n in nums(1).filter(|n| !heads.contains(n)).take(size) {
+                list[len] = n;
+                len += 1;
+            }
+            create_order(&list[..len])
+        };
+
+        if let Some(q) = nums(4).next() {
+            return (Rank::Quads, with_kickers(&[q], 1))
+        }
+        if let Some(t) = nums(3).next() {
+            if let Some(p) = nums(2).find(|&n| n != t) {
+                return (Rank::FullHouse, create_order(&[t, p]))
+            }
+        }
+        if let Some(&mask) = flash {
+            let mut list = [0u32; HAND_SIZE];
+            let top = (0..NUMBER_NUM).rev().filter(|&n| mask & (1 << n) != 0);
+            for (dst, n) in list.iter_mut().zip(top) {
+                *dst = n;
+            }
+            return (Rank::Flash, create_order(&list))
+        }
+        if let Some(order) = find_straight_mask(all) {
+            return (Rank::Straight, order)
+        }
+        if let Some(t) = nums(3).next() {
+            return (Rank::Trips, with_kickers(&[t], 2))
+        }
+        let mut pairs = nums(2);
+        match (pairs.next(), pairs.next()) {
+            (Some(p1), Some(p2)) => (Rank::TwoPair, with_kickers(&[p1, p2], 1)),
+            (Some(p), None) => (Rank::OnePair, with_kickers(&[p], 3)),
+            _ => (Rank::HighCard, with_kickers(&[], HAND_SIZE)),
+        }
+    }
+
+    // [u32; 5..=7] => the 5 cards which make calc_best()
+    // (sorted by number in descending order)
+    pub fn calc_best_cards(cards: &[u32]) -> [u32; HAND_SIZE] {
+        let best = calc_best(cards);
+        let n = cards.len();
+
+        // try all 5-cards subsets (skip n - 5 cards)
+        let mut hand = [0u32; HAND_SIZE];
+        for skip in 0u32..(1 << n) {
+            if skip.count_ones() as usize != n - HAND_SIZE {
+                continue
+            }
+            let picked = cards.iter().enumerate().filter(|(i, _)| skip & (1 << i) == 0);
+            for (dst, (_, &code)) in hand.iter_mut().zip(picked) {
+                *dst = code;
+            }
+            if calc_hand(&hand) == best {
+                hand.sort_unstable_by_key(|&code| (decode(code).1, decode(code).0));
+                hand.reverse();
+                return hand
+            }
+        }
+
+        unreachable!()
+    }
+
+    // number mask => the highest straight order
+    pub(crate) fn find_straight_mask(mask: u32) -> Option<u32> {
+        let five = (1 << HAND_SIZE) - 1;
+        if let Some(high) = (4..NUMBER_NUM).rev().find(|&high| (mask >> (high - 4)) & five == five) {
+            return Some(high)
+        }
+        // A, 5, 4, 3, 2 (order is 5 (=3))
+        let special = (1 << 12) | 0b1111;
+        if mask & special == special {
+            return Some(3)
+        }
+
+        None
+    }
+
+    // nCr (0 if r > n)
+    pub(crate) fn choose(n: u64, r: u64) -> u64 {
+        if r > n {
+            return 0
+        }
+        let r = r.min(n - r);
+        let mut value = 1u64;
+        for i in 0..r {
+            value = value * (n - i) / (i + 1);
+        }
+
+        value
+    }
+
     // number list (len <= 5) => single integer (<= 20 bit)
     // num_list[0] is the most significant.
     fn create_order(num_list: &[u32]) -> u32 {
@@ -157,10 +442,7 @@ mod cards {
             .position(|&count| count == 1)
             .map(|idx| idx as u32);
 
-        match found {
-            None => None,
-            Some(num) => Some(create_order(&[num, kicker.unwrap()]))
-        }
+        found.map(|num| create_order(&[num, kicker.unwrap()]))
     }
 
     fn find_fullhouse(nb: &[i32]) -> Option<u32> {
@@ -232,10 +514,7 @@ mod cards {
         let found = nb.iter()
             .position(|&count| count == 3)
             .map(|idx| idx as u32);
-        if found.is_none() {
-            return None
-        }
-        order[size] = found.unwrap();
+        order[size] = found?;
         size += 1;
 
         // find all idx where nb[idx] != 3 (kicker)
@@ -325,10 +604,10 @@ mod cards {
     }
 
     fn find_highcard(nl_sorted: &[u32]) -> u32 {
-        assert!(nl_sorted.len() == HAND_SIZE as usize);
+        assert!(nl_sorted.len() == HAND_SIZE);
 
         // use sorted (discending) number list
-        create_order(&nl_sorted)
+        create_order(nl_sorted)
     }
 }
 
@@ -374,6 +653,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn card_order() {
+        // 2c < 2d < 2h < 2s < 3c
+        let codes = [cards::encode(0, 0), cards::encode(1, 0), cards::encode(2, 0),
+            cards::encode(3, 0), cards::encode(0, 1)];
+        for (i, &code) in codes.iter().enumerate() {
+            assert_eq!(cards::card_order(code), i as u32);
+        }
+        assert_eq!(cards::card_order(cards::encode(3, 12)), cards::CARDS_NUM - 1);
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(cards::to_text(cards::encode(3, 12)), "As");
+        assert_eq!(cards::to_text(cards::encode(0, 0)), "2c");
+        assert_eq!(cards::to_text(cards::encode(1, 8)), "Td");
+        for code in 0..cards::CARDS_NUM {
+            assert_eq!(cards::from_text(&cards::to_text(code)), Some(code));
+        }
+        assert_eq!(cards::from_text("as"), Some(cards::encode(3, 12)));
+        assert_eq!(cards::from_text("AS"), Some(cards::encode(3, 12)));
+        assert_eq!(cards::from_text("1s"), None);
+        assert_eq!(cards::from_text("Ax"), None);
+        assert_eq!(cards::from_text("A"), None);
+        assert_eq!(cards::from_text("Ass"), None);
+    }
+
+    #[test]
+    fn list_text() {
+        let list = vec![cards::encode(3, 12), cards::encode(2, 11), cards::encode(1, 10)];
+        assert_eq!(cards::list_to_text(&list), "As Kh Qd");
+        assert_eq!(cards::list_from_text("As Kh Qd"), Ok(list.clone()));
+        assert_eq!(cards::list_from_text("AsKhQd"), Ok(list.clone()));
+        assert_eq!(cards::list_from_text(" As,Kh, Qd "), Ok(list));
+        assert_eq!(cards::list_from_text(""), Ok(vec![]));
+        assert_eq!(cards::list_from_text("As Kx"), Err(3));
+        assert_eq!(cards::list_from_text("As K"), Err(3));
+    }
+
     #[test]
     #[should_panic]
     fn decode_invalid() {
@@ -398,6 +716,22 @@ mod tests {
 
     }
 
+    #[test]
+    fn for_each_case() {
+        let my_hand = [
+            cards::encode(0, 12),
+            cards::encode(1, 12),
+        ];
+        let mut result = Vec::new();
+        cards::for_each_case(&my_hand, 2, |case| result.push(case.to_vec()));
+        assert_eq!(result, cards::all_case(&my_hand, 2));
+
+        // fixed cards more than size
+        let mut count = 0;
+        cards::for_each_case(&[0, 1, 2, 3, 4], 1, |_| count += 1);
+        assert_eq!(count, 47);
+    }
+
     #[test]
     fn calc_hand_quads() {
         let mut hand1 = vec![0u32; 0];
@@ -706,4 +1040,75 @@ mod tests {
         let hand = vec![0u32, 1u32, 2u32, 3u32, 100];
         cards::calc_hand(&hand);
     }
+
+    // all k-subsets of 0..n
+    fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+        if k == 0 {
+            return vec![vec![]]
+        }
+        (k - 1..n)
+            .flat_map(|last| {
+                combinations(last, k - 1).into_iter().map(move |mut v| {
+                    v.push(last);
+                    v
+                })
+            })
+            .collect()
+    }
+
+    fn calc_best_brute(cards: &[u32]) -> (cards::Rank, u32) {
+        combinations(cards.len(), cards::HAND_SIZE).iter()
+            .map(|idx| {
+                let hand: Vec<u32> = idx.iter().map(|&i| cards[i]).collect();
+                cards::calc_hand(&hand)
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn calc_best_5() {
+        for hand in cards::all_case(&[], 5).iter().step_by(101) {
+            assert_eq!(cards::calc_best(hand), cards::calc_hand(hand));
+        }
+    }
+
+    #[test]
+    fn calc_best_7() {
+        // suit 0 and 1 (flash, straight flash, full house)
+        let deck1: Vec<u32> = (0..20).collect();
+        // 2, 3, 4, 5, A of all suits (quads, wheel)
+        let deck2: Vec<u32> = (0..4)
+            .flat_map(|s| [0, 1, 2, 3, 12].iter().map(move |&n| cards::encode(s, n)))
+            .collect();
+        for deck in &[deck1, deck2] {
+            for idx in combinations(deck.len(), 7).iter().step_by(7) {
+                let hand: Vec<u32> = idx.iter().map(|&i| deck[i]).collect();
+                assert_eq!(cards::calc_best(&hand), calc_best_brute(&hand));
+                assert_eq!(cards::calc_best(&hand[..6]), calc_best_brute(&hand[..6]));
+            }
+        }
+    }
+
+    #[test]
+    fn calc_best_cards() {
+        // As Ks Qs Js Ts 2c 2d => royal straight flash
+        let hand = [
+            cards::encode(0, 0), cards::encode(3, 11), cards::encode(3, 8),
+            cards::encode(1, 0), cards::encode(3, 12), cards::encode(3, 10),
+            cards::encode(3, 9),
+        ];
+        let best = cards::calc_best_cards(&hand);
+        assert_eq!(best, [
+            cards::encode(3, 12), cards::encode(3, 11), cards::encode(3, 10),
+            cards::encode(3, 9), cards::encode(3, 8),
+        ]);
+        assert_eq!(cards::calc_hand(&best), cards::calc_best(&hand));
+    }
+
+    #[test]
+    #[should_panic]
+    fn calc_best_invalid() {
+        cards::calc_best(&[0, 1, 2, 3]);
+    }
 }
@@ -0,0 +1,261 @@
+// Exact probability of each Rank after the rest cards are dealt
+//
+// "What are the odds of making a flash by the river?"
+// Counts are calculated combinatorially without enumerating every case:
+//
+// - Non-flash hands only depend on how many cards of each number are drawn
+//   (number profile). Each profile has prod C(available, drawn) cases.
+// - With at most 7 cards only one suit can make a flash, and then
+//   quads and full house are impossible. So a flash case is Flash or
+//   StraightFlash, decided by the cards of that suit only.
+//   Flash cases are counted per suit subset and subtracted from the
+//   number profiles.
+use crate::cards::{decode, choose, find_straight_mask, Rank, RANK_NUM, HAND_SIZE, NUMBER_NUM, SUIT_NUM};
+
+const NUMBERS: usize = NUMBER_NUM as usize;
+const SUITS: usize = SUIT_NUM as usize;
+// flash and full house (quads) can not be made at the same time
+const MAX_CARDS: usize = 7;
+
+struct Deck {
+    // known cards count of each number
+    known: [u32; NUMBERS],
+    // known number mask of each suit
+    known_suit: [u32; SUITS],
+    // available number mask of each suit
+    avail_suit: [u32; SUITS],
+}
+
+impl Deck {
+    fn new(known: &[u32]) -> Self {
+        let mut deck = Deck {
+            known: [0; NUMBERS],
+            known_suit: [0; SUITS],
+            avail_suit: [(1 << NUMBER_NUM) - 1; SUITS],
+        };
+        for &code in known {
+            let (s, n) = decode(code);
+            assert!(deck.known_suit[s as usize] & (1 << n) == 0, "duplicate card");
+            deck.known[n as usize] += 1;
+            deck.known_suit[s as usize] |= 1 << n;
+            deck.avail_suit[s as usize] &= !(1 << n);
+        }
+
+        deck
+    }
+
+    // available cards of number n
+    fn avail(&self, n: usize) -> u32 {
+        self.avail_suit.iter().filter(|&&m| m & (1 << n) != 0).count() as u32
+    }
+}
+
+// rank without flash by total count of each number
+fn rank_by_numbers(total: &[u32; NUMBERS]) -> Rank {
+    let count = |c: u32| total.iter().filter(|&&t| t >= c).count();
+    let mask = total.iter().enumerate()
+        .filter(|(_, &t)| t > 0)
+        .fold(0u32, |m, (n, _)| m | (1 << n));
+
+    if count(4) > 0 {
+        Rank::Quads
+    }
+    else if count(3) > 0 && count(2) >= 2 {
+        Rank::FullHouse
+    }
+    else if find_straight_mask(mask).is_some() {
+        Rank::Straight
+    }
+    else if count(3) > 0 {
+        Rank::Trips
+    }
+    else if count(2) >= 2 {
+        Rank::TwoPair
+    }
+    else if count(2) == 1 {
+        Rank::OnePair
+    }
+    else {
+        Rank::HighCard
+    }
+}
+
+// cases of the number profile which make a flash of some suit
+fn flash_cases(deck: &Deck, drawn: &[u32; NUMBERS]) -> u64 {
+    let mut sum = 0;
+    for s in 0..SUITS {
+        let known = deck.known_suit[s].count_ones() as usize;
+        // dp[c]: cases where c cards of suit s are drawn
+        let mut dp = [0u64; MAX_CARDS + 1];
+        dp[0] = 1;
+        for (n, &d) in drawn.iter().enumerate() {
+            let avail = deck.avail(n) as u64;
+            let d = d as u64;
+            let mut next = [0u64; MAX_CARDS + 1];
+            for c in 0..=MAX_CARDS {
+                if dp[c] == 0 {
+                    continue
+                }
+                if deck.avail_suit[s] & (1 << n) != 0 {
+                    // suit s card of number n is not drawn / drawn
+                    next[c] += dp[c] * choose(avail - 1, d);
+                    if d > 0 && c < MAX_CARDS {
+                        next[c + 1] += dp[c] * choose(avail - 1, d - 1);
+                    }
+                }
+                else {
+                    next[c] += dp[c] * choose(avail, d);
+                }
+            }
+            dp = next;
+        }
+        sum += dp.iter().enumerate()
+            .filter(|&(c, _)| known + c >= HAND_SIZE)
+            .map(|(_, &ways)| ways)
+            .sum::<u64>();
+    }
+
+    sum
+}
+
+fn count_profiles(
+    deck: &Deck, n: usize, rest: u32,
+    drawn: &mut [u32; NUMBERS], counts: &mut [u64; RANK_NUM])
+{
+    if n >= NUMBERS {
+        if rest > 0 {
+            return
+        }
+        let cases: u64 = (0..NUMBERS)
+            .map(|i| choose(deck.avail(i) as u64, drawn[i] as u64))
+            .product();
+        let mut total = [0u32; NUMBERS];
+        for i in 0..NUMBERS {
+            total[i] = deck.known[i] + drawn[i];
+        }
+        counts[rank_by_numbers(&total) as usize] += cases - flash_cases(deck, drawn);
+        return
+    }
+
+    for d in 0..=rest.min(deck.avail(n)) {
+        drawn[n] = d;
+        count_profiles(deck, n + 1, rest - d, drawn, counts);
+    }
+    drawn[n] = 0;
+}
+
+// known: known cards (e.g. hole + board), to_come: cards to be dealt
+// known.len() + to_come must be 5..=7
+// => number of cases for each Rank (index = Rank as usize)
+pub fn rank_counts(known: &[u32], to_come: usize) -> [u64; RANK_NUM] {
+    let size = known.len() + to_come;
+    assert!((HAND_SIZE..=MAX_CARDS).contains(&size));
+
+    let deck = Deck::new(known);
+    let mut counts = [0u64; RANK_NUM];
+
+    // non-flash cases
+    let mut drawn = [0u32; NUMBERS];
+    count_profiles(&deck, 0, to_come as u32, &mut drawn, &mut counts);
+
+    // flash cases: choose the suit s cards, then others from the rest suits
+    let all_avail: u32 = deck.avail_suit.iter().map(|m| m.count_ones()).sum();
+    for s in 0..SUITS {
+        let known_mask = deck.known_suit[s];
+        let avail_mask = deck.avail_suit[s];
+        let others = (all_avail - avail_mask.count_ones()) as u64;
+        for sub in 0..(1u32 << NUMBER_NUM) {
+            if sub & !avail_mask != 0 {
+                continue
+            }
+            let j = sub.count_ones() as usize;
+            if j > to_come || known_mask.count_ones() as usize + j < HAND_SIZE {
+                continue
+            }
+            let rank = if find_straight_mask(known_mask | sub).is_some() {
+                Rank::StraightFlash
+            }
+            else {
+                Rank::Flash
+            };
+            counts[rank as usize] += choose(others, (to_come - j) as u64);
+        }
+    }
+
+    counts
+}
+
+// same as rank_counts(), but probabilities (sum = 1.0)
+pub fn rank_distribution(known: &[u32], to_come: usize) -> [f64; RANK_NUM] {
+    let counts = rank_counts(known, to_come);
+    let total: u64 = counts.iter().sum();
+
+    let mut result = [0.0; RANK_NUM];
+    for (r, &c) in result.iter_mut().zip(counts.iter()) {
+        *r = c as f64 / total as f64;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{encode, for_each_case, calc_best};
+
+    fn brute(known: &[u32], to_come: usize) -> [u64; RANK_NUM] {
+        let mut counts = [0u64; RANK_NUM];
+        let mut cards = known.to_vec();
+        for_each_case(known, to_come, |rest| {
+            cards.truncate(known.len());
+            cards.extend_from_slice(rest);
+            counts[calc_best(&cards).0 as usize] += 1;
+        });
+
+        counts
+    }
+
+    #[test]
+    fn five_cards() {
+        assert_eq!(rank_counts(&[], 5), [
+            1_302_540, 1_098_240, 123_552, 54_912, 10_200, 5_108, 3_744, 624, 40,
+        ]);
+    }
+
+    #[test]
+    fn seven_cards() {
+        assert_eq!(rank_counts(&[], 7), [
+            23_294_460, 58_627_800, 31_433_400, 6_461_620, 6_180_020,
+            4_047_644, 3_473_184, 224_848, 41_584,
+        ]);
+    }
+
+    #[test]
+    fn same_as_brute() {
+        let hole = [encode(0, 12), encode(0, 11)];
+        let flop = [encode(0, 10), encode(0, 9), encode(1, 9)];
+        let pair = [encode(2, 5), encode(3, 5), encode(1, 4), encode(2, 3), encode(3, 2)];
+        assert_eq!(rank_counts(&hole, 3), brute(&hole, 3));
+        assert_eq!(rank_counts(&[&hole[..], &flop[..]].concat(), 2),
+            brute(&[&hole[..], &flop[..]].concat(), 2));
+        assert_eq!(rank_counts(&pair, 2), brute(&pair, 2));
+        assert_eq!(rank_counts(&pair[..4], 3), brute(&pair[..4], 3));
+    }
+
+    #[test]
+    fn flash_by_river() {
+        // two diamonds in hand, two on the flop: 9 outs in 47 cards, two to come
+        let known = [encode(1, 12), encode(1, 3), encode(1, 8), encode(1, 0), encode(0, 5)];
+        let dist = rank_distribution(&known, 2);
+        assert!((dist.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        let flash = dist[Rank::Flash as usize] + dist[Rank::StraightFlash as usize];
+        let expected = 1.0 - (38.0 * 37.0) / (47.0 * 46.0);
+        assert!((flash - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many() {
+        rank_counts(&[0, 1, 2, 3, 4, 5], 2);
+    }
+}
@@ -0,0 +1,260 @@
+// Pluggable players (bots) for hold'em
+//
+// A Player sees a PlayerView: its own hole cards and what is public at
+// the table (board, stacks, bets, actions), never the hole cards of others
+// or the deck. play() drives a hand by asking the player of each seat.
+//
+// Reference players:
+//   RandomPlayer: a random legal action
+//   CallingStation: checks or calls, never folds or raises
+//   HandStrengthPlayer: folds, calls or raises by the equity against random hands
+use alloc::boxed::Box;
+use crate::betting::BettingStructure;
+use crate::equity::{equity_vs_random, HOLE_SIZE};
+use crate::holdem::{Action, GameError, HoldemGame, LegalActions, Seat, Street};
+use crate::rng::Rng;
+
+// what a seat is allowed to see
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerView<'a> {
+    pub seat: usize,
+    pub hole: [u32; HOLE_SIZE],
+    pub board: &'a [u32],
+    pub street: Street,
+    pub button: usize,
+    // stacks and bets of every seat
+    pub seats: &'a [Seat],
+    pub pot: u64,
+    pub current_bet: u64,
+    // empty unless the seat is to act
+    pub legal: LegalActions,
+    pub history: &'a [(Street, usize, Action)],
+}
+
+impl PlayerView<'_> {
+    // chips the seat needs to call
+    pub fn to_call(&self) -> u64 {
+        self.legal.call.unwrap_or(0)
+    }
+
+    // other seats still in the hand
+    pub fn opponents(&self) -> usize {
+        self.seats.iter().enumerate()
+            .filter(|&(i, s)| i != self.seat && s.in_hand())
+            .count()
+    }
+}
+
+pub trait Player {
+    // called only for the seat to act, the action should be legal
+    fn act(&mut self, view: &PlayerView) -> Action;
+}
+
+impl<P: Player + ?Sized> Player for Box<P> {
+    fn act(&mut self, view: &PlayerView) -> Action {
+        (**self).act(view)
+    }
+}
+
+impl<P: Player + ?Sized> Player for &mut P {
+    fn act(&mut self, view: &PlayerView) -> Action {
+        (**self).act(view)
+    }
+}
+
+// players: one for each seat (empty seats are never asked)
+// => Err if a player returns an illegal action
+pub fn play<B: BettingStructure, P: Player>(
+    game: &mut HoldemGame<B>, players: &mut [P]) -> Result<(), GameError>
+{
+    assert!(players.len() == game.seats().len());
+
+    while let Some(seat) = game.to_act() {
+        let view = game.view(seat).unwrap();
+        let action = players[seat].act(&view);
+        game.act(action)?;
+    }
+
+    Ok(())
+}
+
+// a random legal action, the amount uniform in the range
+#[derive(Debug, Clone)]
+pub struct RandomPlayer {
+    rng: Rng,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: Rng::new(seed) }
+    }
+
+    fn amount(&mut self, (min, max): (u64, u64)) -> u64 {
+        match (max - min).checked_add(1) {
+            Some(n) => min + self.rng.below(n),
+            None => self.rng.next_u64(),
+        }
+    }
+}
+
+impl Player for RandomPlayer {
+    fn act(&mut self, view: &PlayerView) -> Action {
+        let legal = &view.legal;
+        let mut actions = [Action::Fold; 6];
+        let mut n = 0;
+        let mut push = |action| {
+            actions[n] = action;
+            n += 1;
+        };
+        if legal.fold {
+            push(Action::Fold);
+        }
+        if legal.check {
+            push(Action::Check);
+        }
+        if legal.call.is_some() {
+            push(Action::Call);
+        }
+        if let Some(range) = legal.bet {
+            push(Action::Bet(range.0));
+        }
+        if let Some(range) = legal.raise {
+            push(Action::Raise(range.0));
+        }
+        if legal.all_in.is_some() {
+            push(Action::AllIn);
+        }
+
+        match actions[self.rng.below(n as u64) as usize] {
+            Action::Bet(_) => Action::Bet(self.amount(legal.bet.unwrap())),
+            Action::Raise(_) => Action::Raise(self.amount(legal.raise.unwrap())),
+            action => action,
+        }
+    }
+}
+
+// checks when possible, otherwise calls
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CallingStation;
+
+impl Player for CallingStation {
+    fn act(&mut self, view: &PlayerView) -> Action {
+        if view.legal.check { Action::Check } else { Action::Call }
+    }
+}
+
+// equity against random hands of the opponents in the hand (Monte Carlo):
+// raises the pot at raise_equity or more, calls with pot odds, folds otherwise
+#[derive(Debug, Clone)]
+pub struct HandStrengthPlayer {
+    pub trials: u32,
+    pub raise_equity: f64,
+    rng: Rng,
+}
+
+impl HandStrengthPlayer {
+    pub fn new(seed: u64) -> Self {
+        Self { trials: 500, raise_equity: 0.6, rng: Rng::new(seed) }
+    }
+
+    // the equity of the seat's hand
+    pub fn strength(&mut self, view: &PlayerView) -> f64 {
+        let opponents = view.opponents().clamp(1, 9);
+        equity_vs_random(&view.hole, view.board, opponents, self.trials, self.rng.next_u64())
+    }
+}
+
+impl Player for HandStrengthPlayer {
+    fn act(&mut self, view: &PlayerView) -> Action {
+        let legal = &view.legal;
+        let equity = self.strength(view);
+        let to_call = view.to_call();
+
+        if equity >= self.raise_equity {
+            // the pot after calling, clamped to the range
+            let pot = view.current_bet + view.pot + to_call;
+            if let Some((min, max)) = legal.bet {
+                return Action::Bet(pot.clamp(min, max))
+            }
+            if let Some((min, max)) = legal.raise {
+                return Action::Raise(pot.clamp(min, max))
+            }
+        }
+        if legal.check {
+            return Action::Check
+        }
+        if equity * (view.pot + to_call) as f64 >= to_call as f64 {
+            Action::Call
+        }
+        else {
+            Action::Fold
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+    use crate::holdem::GameConfig;
+
+    const CONFIG: GameConfig = GameConfig::new(1, 2);
+
+    #[test]
+    fn view() {
+        let game = HoldemGame::new(CONFIG, &[100, 100, 0, 100], 0, 1);
+        // seat 1 posts the small blind, seat 3 the big blind
+        let view = game.view(0).unwrap();
+        assert_eq!(view.hole, game.hole(0).unwrap());
+        assert_eq!(view.legal, game.legal_actions());
+        assert_eq!(view.to_call(), 2);
+        assert_eq!(view.opponents(), 2);
+        // not to act: no legal actions
+        let view = game.view(3).unwrap();
+        assert_eq!(view.legal, LegalActions::default());
+        assert!(game.view(2).is_none());
+    }
+
+    #[test]
+    fn random_players() {
+        for seed in 0..50 {
+            let mut game = HoldemGame::new(CONFIG, &[100, 100, 100], 0, seed);
+            let mut players: Vec<RandomPlayer> = (0..3).map(|i| RandomPlayer::new(seed * 3 + i)).collect();
+            play(&mut game, &mut players).unwrap();
+            assert!(game.is_finished());
+            assert_eq!(game.net_results().iter().sum::<i64>(), 0);
+        }
+    }
+
+    #[test]
+    fn calling_stations() {
+        let mut game = HoldemGame::new(CONFIG, &[100, 100, 100], 0, 2);
+        play(&mut game, &mut [CallingStation; 3]).unwrap();
+        assert_eq!(game.street(), Street::Showdown);
+        assert_eq!(game.pot(), 6);
+    }
+
+    #[test]
+    fn hand_strength() {
+        let mut players: Vec<Box<dyn Player>> = vec![
+            Box::new(HandStrengthPlayer::new(1)),
+            Box::new(CallingStation),
+            Box::new(RandomPlayer::new(2)),
+        ];
+        let mut game = HoldemGame::new(CONFIG, &[100, 100, 100], 0, 3);
+        play(&mut game, &mut players).unwrap();
+        assert!(game.is_finished());
+
+        // the nuts raises, seven-deuce folds to a raise
+        let mut bot = HandStrengthPlayer::new(3);
+        let mut game = HoldemGame::new(CONFIG, &[100, 100, 100], 0, 4);
+        let mut view = game.view(0).unwrap();
+        view.hole = [crate::cards::encode(0, 12), crate::cards::encode(1, 12)];
+        assert_eq!(bot.act(&view), Action::Raise(7));
+        game.act(Action::Raise(20)).unwrap();
+        game.act(Action::Call).unwrap();
+        let mut view = game.view(2).unwrap();
+        view.hole = [crate::cards::encode(0, 5), crate::cards::encode(1, 0)];
+        assert_eq!(bot.act(&view), Action::Fold);
+    }
+}
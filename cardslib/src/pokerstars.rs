@@ -0,0 +1,1131 @@
+// PokerStars text hand histories
+//
+// export() writes a HandHistory in the format most tracking software reads:
+//   PokerStars Hand #1: Hold'em No Limit (1/2) - 2026/10/19 12:00:00 ET
+//   Table 'DollsCards' 6-max Seat #1 is the button
+//   Seat 1: Alice (200 in chips)
+//   ...
+//   *** HOLE CARDS *** / *** FLOP *** [..] / ... / *** SHOW DOWN ***
+//   *** SUMMARY ***
+// Cards are written as types::Card ("As"). Amounts are plain chips, and
+// every known hand is "Dealt to" its player. A history the engine could not
+// have produced (e.g. edited JSON) is an ExportError.
+//
+// import() reads such text (files from PokerStars or from export()) back
+// into HandHistory values, one result per hand. Errors have the line
+// number in the whole text. Pots are rebuilt from the chips put in and the
+// "collected" lines, the results from what each player got back, so
+// HandHistory::replay() checks the file against the engine. Actions ending
+// with "and is all-in" are read as Action::AllIn. Rake is not known to the
+// engine: raked hands do not replay.
+use core::convert::TryFrom;
+use core::fmt::{self, Write};
+use alloc::{format, string::String, vec, vec::Vec};
+use crate::cards::list_from_text;
+use crate::equity::{HOLE_SIZE, BOARD_SIZE};
+use crate::history::HandHistory;
+use crate::holdem::{Action, Ante, GameConfig, Post, Street, MAX_SEATS};
+use crate::pot::{build_pots, left_of_button, PotAward, PotBreakdown};
+use crate::types::{hand_description, Card, HandValue};
+
+// what the hand history does not record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarsHeader {
+    pub table: String,
+    // e.g. "Hold'em No Limit", "Hold'em Pot Limit", "Hold'em Limit"
+    pub game: String,
+    // e.g. "2026/10/19 12:00:00 ET"
+    pub time: String,
+    // "6-max", imported hands have a seat for each
+    pub max_seats: usize,
+}
+
+fn card_list(cards: &[Card]) -> String {
+    let texts: Vec<String> = cards.iter().map(|c| format!("{}", c)).collect();
+    format!("[{}]", texts.join(" "))
+}
+
+fn street_name(street: Street) -> &'static str {
+    match street {
+        Street::Preflop => "HOLE CARDS",
+        Street::Flop => "FLOP",
+        Street::Turn => "TURN",
+        _ => "RIVER",
+    }
+}
+
+// "*** TURN *** [2c 3d 4h] [5s]"
+fn street_header(out: &mut String, street: Street, board: &[Card]) {
+    let shown = match street {
+        Street::Flop => 3,
+        Street::Turn => 4,
+        _ => 5,
+    };
+    if board.len() < shown {
+        return
+    }
+    let _ = match street {
+        Street::Flop => writeln!(out, "*** FLOP *** {}", card_list(&board[..3])),
+        _ => writeln!(out, "*** {} *** {} {}", street_name(street), card_list(&board[..shown - 1]),
+            card_list(&board[shown - 1..shown])),
+    };
+}
+
+const STREETS: [Street; 4] = [Street::Preflop, Street::Flop, Street::Turn, Street::River];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportError {
+    pub reason: &'static str,
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid hand history: {}", self.reason)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ExportError {}
+
+// the seats, streets and cards export() relies on (chips are checked while
+// writing)
+fn check_export(h: &HandHistory) -> Result<(), &'static str> {
+    let n = h.stacks.len();
+    let seat = |i: usize| i < n && h.stacks[i] > 0;
+    if h.players.len() != n || h.holes.len() != n || !seat(h.button) {
+        return Err("seats")
+    }
+    if h.board.len() > BOARD_SIZE {
+        return Err("board")
+    }
+    if !h.posts.iter().all(|p| seat(p.0)) {
+        return Err("posts")
+    }
+    if !h.actions.iter().all(|a| seat(a.1) && a.0 <= Street::River) {
+        return Err("actions")
+    }
+    if !h.showdown.iter().all(|s| seat(s.0) && h.holes[s.0].is_some()) {
+        return Err("showdown")
+    }
+    if !h.pots.pots.iter().all(|a| !a.winners.is_empty() && a.winners.iter().all(|&i| seat(i))) {
+        return Err("pots")
+    }
+    Ok(())
+}
+
+// one hand, ending with a line break
+pub fn export(history: &HandHistory, header: &StarsHeader) -> Result<String, ExportError> {
+    let h = history;
+    let fail = |reason| ExportError { reason };
+    check_export(h).map_err(fail)?;
+    let n = h.stacks.len();
+    let name = |i: usize| h.players[i].as_str();
+    let mut out = String::new();
+
+    let _ = writeln!(out, "PokerStars Hand #{}: {} ({}/{}) - {}",
+        h.id, header.game, h.config.small_blind, h.config.big_blind, header.time);
+    // at least the seats of the history, so that every seat can be read back
+    let _ = writeln!(out, "Table '{}' {}-max Seat #{} is the button",
+        header.table, header.max_seats.max(n), h.button + 1);
+    for i in (0..n).filter(|&i| h.stacks[i] > 0) {
+        let _ = writeln!(out, "Seat {}: {} ({} in chips)", i + 1, name(i), h.stacks[i]);
+    }
+
+    // chips behind and the bet of the round of each seat
+    let mut stacks = h.stacks.clone();
+    let mut bets = vec![0u64; n];
+    let mut current = 0;
+    let mut k = 0;
+    while k < h.posts.len() {
+        let (i, post) = h.posts[k];
+        // a returning seat posting both blinds
+        if let (Post::BigBlind(big), Some(&(j, Post::DeadSmallBlind(small)))) = (post, h.posts.get(k + 1)) {
+            if i == j && h.posts[..k].iter().any(|p| matches!(p.1, Post::BigBlind(_))) {
+                let both = big.checked_add(small).ok_or(fail("chips"))?;
+                let _ = writeln!(out, "{}: posts small & big blinds {}", name(i), both);
+                stacks[i] = stacks[i].checked_sub(both).ok_or(fail("chips"))?;
+                bets[i] += big;
+                current = current.max(bets[i]);
+                k += 2;
+                continue
+            }
+        }
+        let (text, amount, live) = match post {
+            Post::Ante(a) => ("the ante", a, false),
+            Post::SmallBlind(a) => ("small blind", a, true),
+            Post::BigBlind(a) => ("big blind", a, true),
+            Post::Straddle(a) => ("straddle", a, true),
+            Post::DeadSmallBlind(a) => ("small blind", a, false),
+            Post::BringIn(a) => ("bring-in", a, true),
+        };
+        stacks[i] = stacks[i].checked_sub(amount).ok_or(fail("chips"))?;
+        if live {
+            bets[i] += amount;
+            current = current.max(bets[i]);
+        }
+        let all_in = if stacks[i] == 0 { " and is all-in" } else { "" };
+        let _ = writeln!(out, "{}: posts {} {}{}", name(i), text, amount, all_in);
+        k += 1;
+    }
+
+    let _ = writeln!(out, "*** HOLE CARDS ***");
+    for i in 0..n {
+        if let Some(hole) = h.holes[i] {
+            let _ = writeln!(out, "Dealt to {} {}", name(i), card_list(&hole));
+        }
+    }
+
+    let mut street = Street::Preflop;
+    let mut folded = vec![None; n];
+    for &(s, i, action) in h.actions.iter() {
+        while street < s {
+            street = STREETS[street as usize + 1];
+            street_header(&mut out, street, &h.board);
+            bets.iter_mut().for_each(|b| *b = 0);
+            current = 0;
+        }
+        let to = match action {
+            Action::Call => current.min(bets[i] + stacks[i]),
+            Action::Bet(to) | Action::Raise(to) => to,
+            Action::AllIn => bets[i] + stacks[i],
+            Action::Fold | Action::Check => bets[i],
+        };
+        let added = to.checked_sub(bets[i]).ok_or(fail("chips"))?;
+        stacks[i] = stacks[i].checked_sub(added).ok_or(fail("chips"))?;
+        let all_in = if stacks[i] == 0 && added > 0 { " and is all-in" } else { "" };
+        let _ = match action {
+            Action::Fold => {
+                folded[i] = Some(street);
+                writeln!(out, "{}: folds", name(i))
+            }
+            Action::Check => writeln!(out, "{}: checks", name(i)),
+            _ if to <= current => writeln!(out, "{}: calls {}{}", name(i), added, all_in),
+            _ if current == 0 => writeln!(out, "{}: bets {}{}", name(i), added, all_in),
+            _ => writeln!(out, "{}: raises {} to {}{}", name(i), to - current, to, all_in),
+        };
+        bets[i] = to;
+        current = current.max(to);
+    }
+
+    // (amount, [(winner, share)]) of each pot
+    let mut pots: Vec<(u64, Vec<(usize, u64)>)> = Vec::new();
+    for award in h.pots.pots.iter() {
+        if award.pot.uncalled {
+            let _ = writeln!(out, "Uncalled bet ({}) returned to {}", award.pot.amount, name(award.winners[0]));
+        }
+        else {
+            pots.push((award.pot.amount, award.winners.iter().copied().zip(award.shares.iter().copied()).collect()));
+        }
+    }
+    // when everyone folds to a bet the engine awards it with the pot
+    if h.showdown.is_empty() && pots.len() == 1 && pots[0].1.len() == 1 {
+        let winner = pots[0].1[0].0;
+        let called = (0..n).filter(|&i| i != winner).map(|i| bets[i]).max().unwrap_or(0);
+        let returned = bets[winner].saturating_sub(called);
+        if returned > 0 {
+            let _ = writeln!(out, "Uncalled bet ({}) returned to {}", returned, name(winner));
+            pots[0].0 = pots[0].0.checked_sub(returned).ok_or(fail("pots"))?;
+            pots[0].1[0].1 = pots[0].1[0].1.checked_sub(returned).ok_or(fail("pots"))?;
+        }
+    }
+    // the rest of the board after all-ins
+    while street < Street::River && h.board.len() > [0, 3, 4][street as usize] {
+        street = STREETS[street as usize + 1];
+        street_header(&mut out, street, &h.board);
+    }
+
+    if !h.showdown.is_empty() {
+        let _ = writeln!(out, "*** SHOW DOWN ***");
+        for &(i, value) in h.showdown.iter() {
+            let hole = h.holes[i].unwrap();
+            let _ = writeln!(out, "{}: shows {} ({})", name(i), card_list(&hole), hand_description(&value));
+        }
+    }
+    let pot_name = |k: usize| match (pots.len(), k) {
+        (1, _) => String::from("pot"),
+        (_, 0) => String::from("main pot"),
+        _ => format!("side pot-{}", k),
+    };
+    let mut won = vec![0u64; n];
+    for (k, (_, shares)) in pots.iter().enumerate() {
+        for &(i, share) in shares.iter() {
+            let _ = writeln!(out, "{} collected {} from {}", name(i), share, pot_name(k));
+            won[i] = won[i].checked_add(share).ok_or(fail("pots"))?;
+        }
+    }
+
+    let _ = writeln!(out, "*** SUMMARY ***");
+    let total = pots.iter().try_fold(0u64, |sum, p| sum.checked_add(p.0)).ok_or(fail("pots"))?;
+    let _ = write!(out, "Total pot {}", total);
+    if pots.len() > 1 {
+        for (k, (amount, _)) in pots.iter().enumerate() {
+            let title = pot_name(k);
+            let _ = write!(out, " {}{} {}.", title[..1].to_uppercase(), &title[1..], amount);
+        }
+    }
+    let _ = writeln!(out, " | Rake 0");
+    if !h.board.is_empty() {
+        let _ = writeln!(out, "Board {}", card_list(&h.board));
+    }
+
+    let dealt: Vec<usize> = left_of_button(n, h.button).into_iter().filter(|&i| h.stacks[i] > 0).collect();
+    let position = |i: usize| -> &'static str {
+        if i == h.button {
+            return " (button)"
+        }
+        // blinds posted by their position, not by returning seats
+        let first = |pred: fn(&Post) -> bool| h.posts.iter().find(|p| pred(&p.1)).map(|p| p.0);
+        if first(|p| matches!(p, Post::SmallBlind(_))) == Some(i) {
+            " (small blind)"
+        }
+        else if first(|p| matches!(p, Post::BigBlind(_))) == Some(i) {
+            " (big blind)"
+        }
+        else {
+            ""
+        }
+    };
+    let mut seats = dealt.clone();
+    seats.sort_unstable();
+    for i in seats {
+        let _ = write!(out, "Seat {}: {}{} ", i + 1, name(i), position(i));
+        let shown = h.showdown.iter().find(|s| s.0 == i);
+        let _ = match (folded[i], shown) {
+            (Some(Street::Preflop), _) => {
+                let bet = h.posts.iter().any(|p| p.0 == i && !matches!(p.1, Post::Ante(_)));
+                writeln!(out, "folded before Flop{}", if bet { "" } else { " (didn't bet)" })
+            }
+            (Some(s), _) => {
+                let s = match s { Street::Flop => "Flop", Street::Turn => "Turn", _ => "River" };
+                writeln!(out, "folded on the {}", s)
+            }
+            (None, Some(&(_, value))) if won[i] > 0 => writeln!(out, "showed {} and won ({}) with {}",
+                card_list(&h.holes[i].unwrap()), won[i], hand_description(&value)),
+            (None, Some(&(_, value))) => writeln!(out, "showed {} and lost with {}",
+                card_list(&h.holes[i].unwrap()), hand_description(&value)),
+            (None, None) => writeln!(out, "collected ({})", won[i]),
+        };
+    }
+
+    Ok(out)
+}
+
+// hands separated by blank lines
+pub fn export_all(histories: &[HandHistory], header: &StarsHeader) -> Result<String, ExportError> {
+    let hands = histories.iter().map(|h| export(h, header)).collect::<Result<Vec<String>, _>>()?;
+    Ok(hands.join("\n\n"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportError {
+    // from 1, in the whole text
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ImportError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarsHand {
+    pub header: StarsHeader,
+    pub history: HandHistory,
+    // money amounts ("$0.25") were read in cents
+    pub cents: bool,
+}
+
+fn is_hand_start(line: &str) -> bool {
+    line.starts_with("PokerStars ") && line.contains("Hand #")
+}
+
+// every hand of the text in order, a hand with an error does not stop the
+// others
+pub fn import(text: &str) -> Vec<Result<StarsHand, ImportError>> {
+    let lines: Vec<(usize, &str)> = text.lines().enumerate()
+        .map(|(k, line)| (k + 1, line.trim_start_matches('\u{feff}').trim_end()))
+        .collect();
+
+    let mut hands = Vec::new();
+    let mut start = None;
+    for (k, &(number, line)) in lines.iter().enumerate() {
+        if is_hand_start(line) {
+            if let Some(s) = start {
+                hands.push(parse_hand(&lines[s..k]));
+            }
+            start = Some(k);
+        }
+        else if start.is_none() && hands.is_empty() && !line.is_empty() {
+            hands.push(Err(ImportError { line: number, reason: "not a hand history" }));
+        }
+    }
+    if let Some(s) = start {
+        hands.push(parse_hand(&lines[s..]));
+    }
+
+    hands
+}
+
+// "1500", or "$0.25" in cents
+fn parse_amount(text: &str, cents: bool) -> Option<u64> {
+    let text = text.trim_start_matches(['$', '€', '£']);
+    let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(whole) || !(fraction.is_empty() || cents && fraction.len() <= 2 && digits(fraction)) {
+        return None
+    }
+    let whole: u64 = whole.parse().ok()?;
+    if !cents {
+        return Some(whole)
+    }
+    let fraction = match fraction.len() {
+        0 => 0,
+        1 => fraction.parse::<u64>().ok()? * 10,
+        _ => fraction.parse().ok()?,
+    };
+
+    whole.checked_mul(100)?.checked_add(fraction)
+}
+
+// "[2c 3d 4h] [5s]" => [2c, 3d, 4h, 5s]
+fn bracket_cards(text: &str) -> Option<Vec<Card>> {
+    let mut cards = Vec::new();
+    for group in text.split('[').skip(1) {
+        let (list, _) = group.split_once(']')?;
+        cards.extend(list_from_text(list).ok()?.into_iter().map(Card::new));
+    }
+
+    Some(cards)
+}
+
+// cards seen for the first time, used: the cards seen before
+fn deal(used: &mut u64, cards: &[Card]) -> Result<(), &'static str> {
+    for card in cards {
+        if *used >> card.code() & 1 == 1 {
+            return Err("duplicate card")
+        }
+        *used |= 1 << card.code();
+    }
+    Ok(())
+}
+
+fn set_hole(
+    holes: &mut [Option<[Card; HOLE_SIZE]>], used: &mut u64, i: usize, cards: &[Card]) -> Result<(), &'static str>
+{
+    if cards.len() != HOLE_SIZE {
+        return Err("hole cards")
+    }
+    let hole = [cards[0], cards[1]];
+    match holes[i] {
+        Some(known) if known != hole => Err("hole cards differ"),
+        Some(_) => Ok(()),
+        None => {
+            deal(used, &hole)?;
+            holes[i] = Some(hole);
+            Ok(())
+        }
+    }
+}
+
+// seat i puts value chips in the hand, not more than its stack
+fn put(committed: &mut [u64], stacks: &[u64], i: usize, value: u64) -> Result<(), &'static str> {
+    let total = committed[i].checked_add(value).ok_or("amount")?;
+    if total > stacks[i] {
+        return Err("more than the stack")
+    }
+    committed[i] = total;
+    Ok(())
+}
+
+// the seat whose name starts the line (the longest name) and the rest
+fn player_line<'a>(players: &[String], line: &'a str) -> Option<(usize, &'a str)> {
+    players.iter().enumerate()
+        .filter(|(_, name)| !name.is_empty() && line.starts_with(name.as_str()))
+        .max_by_key(|(_, name)| name.len())
+        .map(|(i, name)| (i, &line[name.len()..]))
+}
+
+// "Seat 3" of a table of n seats => 2
+fn seat_number(text: &str, n: usize) -> Option<usize> {
+    text.parse::<usize>().ok().filter(|&i| (1..=n).contains(&i)).map(|i| i - 1)
+}
+
+// lines of one hand, the first one is the "PokerStars Hand #" line
+fn parse_hand(lines: &[(usize, &str)]) -> Result<StarsHand, ImportError> {
+    let mut lines = lines.iter().copied().filter(|l| !l.1.is_empty()).peekable();
+    let (number, first) = lines.next().unwrap();
+    let err = |line: usize, reason: &'static str| ImportError { line, reason };
+
+    // PokerStars Hand #1: Hold'em No Limit (1/2) - 2026/10/19 12:00:00 ET
+    let text = &first[first.find("Hand #").unwrap() + "Hand #".len()..];
+    let (id, text) = text.split_once(':').ok_or(err(number, "hand number"))?;
+    let id = id.trim().parse().map_err(|_| err(number, "hand number"))?;
+    let close = text.rfind(')').ok_or(err(number, "blinds"))?;
+    let open = text[..close].rfind('(').ok_or(err(number, "blinds"))?;
+    // "1/2", "$0.01/$0.02 USD"
+    let blinds = text[open + 1..close].split_whitespace().next().unwrap_or("");
+    let cents = blinds.contains(|c: char| !c.is_ascii_digit() && c != '/');
+    let (small, big) = blinds.split_once('/').ok_or(err(number, "blinds"))?;
+    let small_blind = parse_amount(small, cents).ok_or(err(number, "blinds"))?;
+    let big_blind = parse_amount(big, cents).filter(|&b| b > 0).ok_or(err(number, "blinds"))?;
+    if !GameConfig::new(small_blind, big_blind).is_valid() {
+        return Err(err(number, "blinds"))
+    }
+    let game = text[..open].trim().into();
+    let time = text[close + 1..].trim_start().trim_start_matches('-').trim().into();
+
+    // Table 'DollsCards' 6-max Seat #1 is the button
+    let (number, line) = lines.next().ok_or(err(number, "no table"))?;
+    let table_err = err(number, "table");
+    let open = line.find('\'').ok_or(table_err.clone())?;
+    let close = line.rfind('\'').filter(|&c| c > open).ok_or(table_err.clone())?;
+    let table = line[open + 1..close].into();
+    let rest = &line[close + 1..];
+    let max_seats: usize = rest.split_whitespace()
+        .find_map(|w| w.strip_suffix("-max"))
+        .and_then(|m| m.parse().ok())
+        .filter(|m| (2..=MAX_SEATS).contains(m))
+        .ok_or(table_err.clone())?;
+    let button = rest.split("Seat #").nth(1)
+        .and_then(|s| s.split_whitespace().next())
+        .and_then(|s| seat_number(s, max_seats))
+        .ok_or(table_err)?;
+
+    // Seat 1: Alice (200 in chips), players sitting out are not dealt in
+    let n = max_seats;
+    let mut players = vec![String::new(); n];
+    let mut stacks = vec![0u64; n];
+    while let Some(&(number, line)) = lines.peek() {
+        // players may be called "Seat 1" too
+        let (seat, rest) = match line.strip_prefix("Seat ").and_then(|l| l.split_once(": ")) {
+            Some(seat) if line.contains(" in chips") => seat,
+            _ => break,
+        };
+        lines.next();
+        let i = seat_number(seat, n).ok_or(err(number, "seat"))?;
+        let paren = rest.rfind(" (").filter(|&p| rest[p..].contains(" in chips")).ok_or(err(number, "seat"))?;
+        let stack = rest[paren + 2..].split([' ', ')']).next()
+            .and_then(|s| parse_amount(s, cents))
+            .ok_or(err(number, "stack"))?;
+        if !rest.ends_with("is sitting out") {
+            players[i] = rest[..paren].into();
+            stacks[i] = stack;
+        }
+    }
+
+    let mut posts: Vec<(usize, Post)> = Vec::new();
+    let mut actions = Vec::new();
+    let mut holes = vec![None; n];
+    let mut board: Vec<Card> = Vec::new();
+    // the board and the hole cards, each card once
+    let mut used = 0u64;
+    // chips put in the hand and in the round
+    let mut committed = vec![0u64; n];
+    let mut bets = vec![0u64; n];
+    let mut folded = vec![false; n];
+    // chips got back, and the seats collecting each pot
+    let mut collected = vec![0u64; n];
+    let mut collectors: Vec<Vec<usize>> = Vec::new();
+    // None before the hole cards
+    let mut street: Option<Street> = None;
+    let mut summary = false;
+    let mut last = number;
+
+    for (number, line) in lines {
+        last = number;
+        let err = |reason| ImportError { line: number, reason };
+        let amount = |text: &str| parse_amount(text, cents).ok_or(err("amount"));
+
+        // *** FLOP *** [2c 8d 9h]
+        if let Some(title) = line.strip_prefix("*** ") {
+            let (name, rest) = title.split_once(" ***").ok_or(err("section"))?;
+            let next = match name {
+                "HOLE CARDS" => Street::Preflop,
+                "FLOP" => Street::Flop,
+                "TURN" => Street::Turn,
+                "RIVER" => Street::River,
+                "SHOW DOWN" => Street::Showdown,
+                "SUMMARY" => {
+                    summary = true;
+                    continue
+                }
+                // e.g. running the board twice
+                _ => return Err(err("unsupported section")),
+            };
+            if matches!(next, Street::Flop | Street::Turn | Street::River) {
+                let cards = bracket_cards(rest).ok_or(err("cards"))?;
+                if cards.len() != next as usize + 2 || !cards.starts_with(&board) {
+                    return Err(err("board"))
+                }
+                deal(&mut used, &cards[board.len()..]).map_err(err)?;
+                board = cards;
+                bets.iter_mut().for_each(|b| *b = 0);
+            }
+            street = Some(next);
+            continue
+        }
+
+        if summary {
+            // Board [..], Seat 2: Bob (small blind) showed [Ks Kh] and lost with ...
+            if let Some(rest) = line.strip_prefix("Board ") {
+                let cards = bracket_cards(rest).ok_or(err("cards"))?;
+                if board.is_empty() && cards.len() <= BOARD_SIZE {
+                    deal(&mut used, &cards).map_err(err)?;
+                    board = cards;
+                }
+                else if cards != board {
+                    return Err(err("board"))
+                }
+            }
+            else if let Some((seat, rest)) = line.strip_prefix("Seat ").and_then(|l| l.split_once(": ")) {
+                let shown = rest.find(" showed [").or_else(|| rest.find(" mucked ["));
+                if let Some(p) = shown {
+                    let i = seat_number(seat, n).ok_or(err("seat"))?;
+                    let cards = bracket_cards(&rest[p..]).ok_or(err("cards"))?;
+                    set_hole(&mut holes, &mut used, i, &cards).map_err(err)?;
+                }
+            }
+            continue
+        }
+
+        if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+            let (value, name) = rest.split_once(") returned to ").ok_or(err("uncalled bet"))?;
+            let i = players.iter().position(|p| !p.is_empty() && p == name).ok_or(err("unknown player"))?;
+            collected[i] = collected[i].checked_add(amount(value)?).ok_or(err("amount"))?;
+            continue
+        }
+        if let Some(rest) = line.strip_prefix("Dealt to ") {
+            let (i, rest) = player_line(&players, rest).ok_or(err("unknown player"))?;
+            if rest.contains('[') {
+                let cards = bracket_cards(rest).ok_or(err("cards"))?;
+                set_hole(&mut holes, &mut used, i, &cards).map_err(err)?;
+            }
+            continue
+        }
+        // chat, players joining or leaving, ...
+        let (i, rest) = match player_line(&players, line) {
+            Some(found) => found,
+            None => continue,
+        };
+        // Alice collected 202 from pot
+        if let Some(rest) = rest.strip_prefix(" collected ") {
+            let (value, pot) = rest.split_once(" from ").ok_or(err("collected"))?;
+            collected[i] = collected[i].checked_add(amount(value)?).ok_or(err("amount"))?;
+            let k = match pot {
+                "pot" | "main pot" => 0,
+                _ => pot.strip_prefix("side pot-").and_then(|k| k.parse().ok()).ok_or(err("pot"))?,
+            };
+            // a pot for each seat at most
+            if k >= n {
+                return Err(err("pot"))
+            }
+            if collectors.len() <= k {
+                collectors.resize(k + 1, Vec::new());
+            }
+            collectors[k].push(i);
+            continue
+        }
+        let rest = match rest.strip_prefix(": ") {
+            Some(rest) => rest,
+            None => continue,
+        };
+
+        let (rest, all_in) = match rest.strip_suffix(" and is all-in") {
+            Some(rest) => (rest, true),
+            None => (rest, false),
+        };
+        let (verb, arg) = rest.split_once(' ').unwrap_or((rest, ""));
+        let action = match verb {
+            "posts" => {
+                // posts small blind 1, posts the ante 1, posts small & big blinds 3
+                let (kind, value) = arg.rsplit_once(' ').ok_or(err("post"))?;
+                let value = amount(value)?;
+                let first_small = !posts.iter().any(|p| matches!(p.1, Post::SmallBlind(_)));
+                put(&mut committed, &stacks, i, value).map_err(err)?;
+                match kind {
+                    "the ante" => posts.push((i, Post::Ante(value))),
+                    "small blind" if first_small => {
+                        bets[i] += value;
+                        posts.push((i, Post::SmallBlind(value)));
+                    }
+                    "small blind" => posts.push((i, Post::DeadSmallBlind(value))),
+                    "big blind" => {
+                        bets[i] += value;
+                        posts.push((i, Post::BigBlind(value)));
+                    }
+                    "small & big blinds" => {
+                        let small = value.checked_sub(big_blind).ok_or(err("post"))?;
+                        bets[i] += big_blind;
+                        posts.push((i, Post::BigBlind(big_blind)));
+                        posts.push((i, Post::DeadSmallBlind(small)));
+                    }
+                    // more than the big blind, like the engine
+                    "straddle" if value > big_blind => {
+                        bets[i] += value;
+                        posts.push((i, Post::Straddle(value)));
+                    }
+                    "straddle" => return Err(err("straddle")),
+                    _ => return Err(err("unknown post")),
+                }
+                continue
+            }
+            // shows [As Ah] (a pair of Aces)
+            "shows" => {
+                let cards = bracket_cards(arg).ok_or(err("cards"))?;
+                set_hole(&mut holes, &mut used, i, &cards).map_err(err)?;
+                continue
+            }
+            "mucks" | "doesn't" | "sits" | "is" | "has" | "leaves" | "joins" => continue,
+            "folds" => {
+                // the folded cards may be shown
+                if arg.contains('[') {
+                    let cards = bracket_cards(arg).ok_or(err("cards"))?;
+                    set_hole(&mut holes, &mut used, i, &cards).map_err(err)?;
+                }
+                folded[i] = true;
+                Action::Fold
+            }
+            "checks" => Action::Check,
+            "calls" => {
+                let value = amount(arg)?;
+                put(&mut committed, &stacks, i, value).map_err(err)?;
+                bets[i] += value;
+                Action::Call
+            }
+            "bets" => {
+                let value = amount(arg)?;
+                put(&mut committed, &stacks, i, value).map_err(err)?;
+                bets[i] += value;
+                Action::Bet(bets[i])
+            }
+            // raises 4 to 6
+            "raises" => {
+                let to = arg.split_once(" to ").ok_or(err("raise"))?.1;
+                let to = amount(to)?;
+                put(&mut committed, &stacks, i, to.checked_sub(bets[i]).ok_or(err("raise"))?).map_err(err)?;
+                bets[i] = to;
+                Action::Raise(to)
+            }
+            _ => return Err(err("unknown action")),
+        };
+        let street = match street {
+            Some(street) if street != Street::Showdown => street,
+            _ => return Err(err("action outside a betting round")),
+        };
+        actions.push((street, i, if all_in { Action::AllIn } else { action }));
+    }
+
+    let err = |reason| ImportError { line: last, reason };
+    if !summary {
+        return Err(err("no summary"))
+    }
+
+    // posts in the order of the engine: antes, blinds, the ante of the
+    // big blind, the straddle, then the blinds of returning seats
+    let big_seat = posts.iter().find(|p| matches!(p.1, Post::BigBlind(_))).map(|p| p.0);
+    let antes: Vec<(usize, u64)> = posts.iter()
+        .filter_map(|&(i, post)| match post { Post::Ante(a) => Some((i, a)), _ => None })
+        .collect();
+    let ante = match antes.as_slice() {
+        [] => Ante::None,
+        [(i, a)] if Some(*i) == big_seat => Ante::BigBlind(*a),
+        _ => Ante::Each(antes.iter().map(|a| a.1).max().unwrap()),
+    };
+    let mut regular_big = true;
+    let mut keyed: Vec<((usize, usize), (usize, Post))> = posts.iter().enumerate()
+        .map(|(k, &(i, post))| {
+            let key = match post {
+                Post::Ante(_) if matches!(ante, Ante::BigBlind(_)) => (3, 0),
+                Post::Ante(_) => (0, i),
+                Post::SmallBlind(_) => (1, 0),
+                Post::BigBlind(_) if regular_big => {
+                    regular_big = false;
+                    (2, 0)
+                }
+                Post::Straddle(_) => (4, 0),
+                _ => (5, k),
+            };
+            (key, (i, post))
+        })
+        .collect();
+    keyed.sort_by_key(|p| p.0);
+    let posts: Vec<(usize, Post)> = keyed.into_iter().map(|p| p.1).collect();
+    let straddle = posts.iter().find_map(|p| match p.1 { Post::Straddle(s) => Some(s), _ => None }).unwrap_or(0);
+    let config = GameConfig { small_blind, big_blind, ante, straddle };
+
+    // pots of the chips put in, split between the seats collecting them
+    let live: Vec<bool> = (0..n).map(|i| stacks[i] > 0 && !folded[i]).collect();
+    if !live.iter().any(|&l| l) {
+        return Err(err("no live player"))
+    }
+    let odd_order = left_of_button(n, button);
+    let mut pots = PotBreakdown { pots: Vec::new(), payouts: vec![0; n] };
+    let mut k = 0;
+    for pot in build_pots(&committed, &live) {
+        let winners: Vec<usize> = if pot.uncalled {
+            pot.eligible.clone()
+        }
+        else {
+            k += 1;
+            let seats = collectors.get(k - 1).ok_or(err("pot without a winner"))?;
+            odd_order.iter().copied().filter(|i| seats.contains(i)).collect()
+        };
+        if winners.is_empty() || !winners.iter().all(|i| pot.eligible.contains(i)) {
+            return Err(err("pot winners"))
+        }
+        let count = winners.len() as u64;
+        let odd_chips = pot.amount % count;
+        let shares: Vec<u64> = (0..count)
+            .map(|j| pot.amount / count + if j < odd_chips { 1 } else { 0 })
+            .collect();
+        for (&i, &share) in winners.iter().zip(&shares) {
+            pots.payouts[i] += share;
+        }
+        pots.pots.push(PotAward { pot, winners, shares, odd_chips });
+    }
+    if k != collectors.len() {
+        return Err(err("pots"))
+    }
+
+    let showdown = if street == Some(Street::Showdown) {
+        if board.len() != BOARD_SIZE {
+            return Err(err("board"))
+        }
+        odd_order.iter().copied()
+            .filter(|&i| live[i])
+            .filter_map(|i| holes[i].map(|hole: [Card; HOLE_SIZE]| {
+                let codes: Vec<u32> = hole.iter().chain(board.iter()).map(|c| c.code()).collect();
+                (i, HandValue::of(&codes))
+            }))
+            .collect()
+    }
+    else {
+        Vec::new()
+    };
+    let net = (0..n)
+        .map(|i| Some(i64::try_from(collected[i]).ok()? - i64::try_from(committed[i]).ok()?))
+        .collect::<Option<Vec<i64>>>()
+        .ok_or(err("amount"))?;
+
+    Ok(StarsHand {
+        header: StarsHeader { table, game, time, max_seats },
+        history: HandHistory {
+            id, config, button, players, stacks, holes, posts, actions, board, showdown, pots, net,
+        },
+        cents,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::betting::NoLimit;
+    use crate::holdem::HoldemGame;
+    use crate::history::seat_names;
+    use crate::cards::encode;
+
+    fn header() -> StarsHeader {
+        StarsHeader {
+            table: "DollsCards".into(),
+            game: "Hold'em No Limit".into(),
+            time: "2026/10/19 12:00:00 ET".into(),
+            max_seats: 6,
+        }
+    }
+
+    #[test]
+    fn showdown_hand() {
+        // Alice (button) AsAh, Bob (small blind) KsKh, Carol (big blind) 7c2d
+        // board 2c 8d 9h Js 3c
+        let deal = [
+            encode(3, 11), encode(0, 5), encode(3, 12),
+            encode(2, 11), encode(1, 0), encode(2, 12),
+            encode(0, 0), encode(1, 6), encode(2, 7), encode(3, 9), encode(0, 1),
+        ];
+        let mut game = HoldemGame::with_deck(GameConfig::new(1, 2), crate::betting::NoLimit, &[200, 100, 150], 0, &deal);
+        game.act(Action::Raise(6)).unwrap();
+        game.act(Action::Raise(20)).unwrap();
+        game.act(Action::Fold).unwrap();
+        game.act(Action::Call).unwrap();
+        game.act(Action::Check).unwrap();
+        game.act(Action::Bet(30)).unwrap();
+        game.act(Action::AllIn).unwrap();
+        game.act(Action::Call).unwrap();
+        assert!(game.is_finished());
+
+        let names = vec!["Alice".into(), "Bob".into(), "Carol".into()];
+        let history = HandHistory::from_game(&game, 7, names);
+        let text = export(&history, &header()).unwrap();
+        let expected = "\
+PokerStars Hand #7: Hold'em No Limit (1/2) - 2026/10/19 12:00:00 ET
+Table 'DollsCards' 6-max Seat #1 is the button
+Seat 1: Alice (200 in chips)
+Seat 2: Bob (100 in chips)
+Seat 3: Carol (150 in chips)
+Bob: posts small blind 1
+Carol: posts big blind 2
+*** HOLE CARDS ***
+Dealt to Alice [As Ah]
+Dealt to Bob [Ks Kh]
+Dealt to Carol [7c 2d]
+Alice: raises 4 to 6
+Bob: raises 14 to 20
+Carol: folds
+Alice: calls 14
+*** FLOP *** [2c 8d 9h]
+Bob: checks
+Alice: bets 30
+Bob: raises 50 to 80 and is all-in
+Alice: calls 50
+*** TURN *** [2c 8d 9h] [Js]
+*** RIVER *** [2c 8d 9h Js] [3c]
+*** SHOW DOWN ***
+Bob: shows [Ks Kh] (a pair of Kings)
+Alice: shows [As Ah] (a pair of Aces)
+Alice collected 202 from pot
+*** SUMMARY ***
+Total pot 202 | Rake 0
+Board [2c 8d 9h Js 3c]
+Seat 1: Alice (button) showed [As Ah] and won (202) with a pair of Aces
+Seat 2: Bob (small blind) showed [Ks Kh] and lost with a pair of Kings
+Seat 3: Carol (big blind) folded before Flop
+";
+        assert_eq!(text, expected);
+
+        // the empty seats of the table are read back
+        let hand = imported(&text);
+        assert_eq!(hand.header, header());
+        assert_eq!(hand.history.stacks, vec![200, 100, 150, 0, 0, 0]);
+        assert_eq!(hand.history.replay(NoLimit).unwrap().net_results(), vec![102, -100, -2, 0, 0, 0]);
+    }
+
+    #[test]
+    fn random_hands() {
+        use crate::player::{play, RandomPlayer};
+        for seed in 0..50 {
+            let mut game = HoldemGame::new(GameConfig::new(1, 2), &[100, 60, 0, 150], 0, seed);
+            let mut players: Vec<RandomPlayer> = (0..4).map(|i| RandomPlayer::new(seed * 4 + i)).collect();
+            play(&mut game, &mut players).unwrap();
+            let history = HandHistory::from_game(&game, seed, seat_names(4));
+            let text = export(&history, &header()).unwrap();
+            // every seat in the summary, the collected chips add up
+            let summary = &text[text.find("*** SUMMARY ***").unwrap()..];
+            assert_eq!(summary.lines().filter(|l| l.starts_with("Seat ")).count(), 3);
+            let collected: u64 = text[..text.len() - summary.len()].lines()
+                .filter_map(|l| l.split(" collected ").nth(1))
+                .map(|l| l.split(' ').next().unwrap().parse::<u64>().unwrap())
+                .sum();
+            let total = summary.lines().nth(1).unwrap().split(' ').nth(2).unwrap().parse::<u64>().unwrap();
+            assert_eq!(collected, total);
+        }
+    }
+
+    fn imported(text: &str) -> StarsHand {
+        let mut hands = import(text);
+        assert_eq!(hands.len(), 1);
+        hands.pop().unwrap().unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        use crate::player::{play, RandomPlayer};
+        let configs = [
+            GameConfig::new(1, 2),
+            GameConfig { small_blind: 1, big_blind: 2, ante: Ante::Each(1), straddle: 4 },
+            GameConfig { small_blind: 5, big_blind: 10, ante: Ante::BigBlind(10), straddle: 0 },
+        ];
+        for seed in 0..60 {
+            let config = configs[seed as usize % 3];
+            let mut game = HoldemGame::new(config, &[100, 60, 0, 150, 80], 4, seed);
+            // a returning seat (not the straddle)
+            if seed % 4 == 0 && config.straddle == 0 {
+                game.post_missed_blinds(3, true, seed % 8 == 0).unwrap();
+            }
+            let mut players: Vec<RandomPlayer> = (0..5).map(|i| RandomPlayer::new(seed * 5 + i)).collect();
+            play(&mut game, &mut players).unwrap();
+            let mut names = seat_names(5);
+            names[2].clear();
+            let history = HandHistory::from_game(&game, seed, names);
+
+            let header = StarsHeader { max_seats: 5, ..header() };
+            let hand = imported(&export(&history, &header).unwrap());
+            assert_eq!(hand.header, header);
+            assert!(!hand.cents);
+            // "and is all-in" is read as Action::AllIn
+            let mut expected = history.clone();
+            for (a, b) in expected.actions.iter_mut().zip(&hand.history.actions) {
+                if b.2 == Action::AllIn {
+                    a.2 = Action::AllIn;
+                }
+            }
+            assert_eq!(hand.history, expected);
+            assert!(hand.history.replay(NoLimit).is_ok());
+        }
+    }
+
+    const CASH_HAND: &str = "\
+PokerStars Hand #212345678901: Hold'em No Limit ($0.01/$0.02 USD) - 2020/05/01 20:15:42 CET [2020/05/01 14:15:42 ET]
+Table 'Aludra III' 6-max Seat #2 is the button
+Seat 1: fish77 ($2.13 in chips)
+Seat 2: Hero ($2 in chips)
+Seat 3: nit: the rock ($1.50 in chips)
+Seat 4: away ($3 in chips) is sitting out
+Seat 5: reg ($4.07 in chips)
+nit: the rock: posts small blind $0.01
+reg: posts big blind $0.02
+*** HOLE CARDS ***
+Dealt to Hero [Ac Qd]
+fish77: calls $0.02
+Hero: raises $0.06 to $0.08
+nit: the rock: folds
+reg: folds
+fish77 said, \"nh\"
+fish77: calls $0.06
+*** FLOP *** [Qs 7h 2c]
+fish77: checks
+Hero: bets $0.10
+fish77: folds
+Uncalled bet ($0.10) returned to Hero
+Hero collected $0.19 from pot
+Hero: doesn't show hand
+*** SUMMARY ***
+Total pot $0.19 | Rake $0
+Board [Qs 7h 2c]
+Seat 1: fish77 folded on the Flop
+Seat 2: Hero (button) collected ($0.19)
+Seat 3: nit: the rock (small blind) folded before Flop
+Seat 5: reg (big blind) folded before Flop
+";
+
+    #[test]
+    fn cash_hand() {
+        let hand = imported(CASH_HAND);
+        assert!(hand.cents);
+        assert_eq!(hand.header.table, "Aludra III");
+        assert_eq!(hand.header.game, "Hold'em No Limit");
+        assert_eq!(hand.header.time, "2020/05/01 20:15:42 CET [2020/05/01 14:15:42 ET]");
+        assert_eq!(hand.header.max_seats, 6);
+
+        let h = &hand.history;
+        assert_eq!(h.id, 212345678901);
+        assert_eq!(h.config, GameConfig::new(1, 2));
+        assert_eq!(h.button, 1);
+        assert_eq!(h.stacks, vec![213, 200, 150, 0, 407, 0]);
+        assert_eq!(h.players[2], "nit: the rock");
+        assert_eq!(h.holes[1].map(|c| card_list(&c)), Some("[Ac Qd]".into()));
+        assert_eq!(h.holes[0], None);
+        assert_eq!(h.actions[1], (Street::Preflop, 1, Action::Raise(8)));
+        assert_eq!(h.actions[2], (Street::Preflop, 2, Action::Fold));
+        assert_eq!(h.actions[6], (Street::Flop, 1, Action::Bet(10)));
+        assert_eq!(h.net, vec![-8, 11, -1, 0, -2, 0]);
+        assert!(h.showdown.is_empty());
+
+        let game = h.replay(NoLimit).unwrap();
+        assert_eq!(game.net_results(), h.net);
+    }
+
+    #[test]
+    fn errors() {
+        // the second hand has an unknown action, the third is fine
+        let bad = CASH_HAND.replace("fish77: checks", "fish77: dances");
+        let text = format!("{}\n\n{}\n\n{}", CASH_HAND, bad, CASH_HAND);
+        let hands = import(&text);
+        assert_eq!(hands.len(), 3);
+        assert!(hands[0].is_ok() && hands[2].is_ok());
+        let lines = CASH_HAND.lines().count() + 2;
+        let error = ImportError { line: lines + 19, reason: "unknown action" };
+        assert_eq!(hands[1].clone().unwrap_err(), error);
+        assert_eq!(error.to_string(), format!("line {}: unknown action", lines + 19));
+
+        let error = |text: &str| import(text).pop().unwrap().unwrap_err();
+        assert_eq!(error("hello\n"), ImportError { line: 1, reason: "not a hand history" });
+        assert_eq!(error(&CASH_HAND.replace("($0.01/$0.02 USD)", "")).reason, "blinds");
+        assert_eq!(error(&CASH_HAND.replace("$0.01/$0.02", "$0.02/$0.01")), ImportError { line: 1, reason: "blinds" });
+        let straddle = CASH_HAND.replace("*** HOLE CARDS ***", "fish77: posts straddle $0.02\n*** HOLE CARDS ***");
+        assert_eq!(error(&straddle), ImportError { line: 10, reason: "straddle" });
+        assert_eq!(error(&CASH_HAND.replace("Seat #2", "Seat #9")), ImportError { line: 2, reason: "table" });
+        assert_eq!(error(&CASH_HAND.replace("[Qs 7h 2c]", "[Qs 7h]")).line, 18);
+        assert_eq!(error(&CASH_HAND.replace("Hero collected", "Bob collected")).reason, "pot without a winner");
+        assert_eq!(error(&CASH_HAND[..CASH_HAND.find("*** SUMMARY").unwrap()]).reason, "no summary");
+        // each card is dealt once
+        let duplicate = |from: &str, to: &str| error(&CASH_HAND.replace(from, to));
+        assert_eq!(duplicate("[Qs 7h 2c]", "[Qs Qs Qs]"), ImportError { line: 18, reason: "duplicate card" });
+        assert_eq!(duplicate("[Ac Qd]", "[Qs Qs]"), ImportError { line: 11, reason: "duplicate card" });
+        assert_eq!(duplicate("[Ac Qd]", "[Ac Qs]"), ImportError { line: 18, reason: "duplicate card" });
+        let shown = CASH_HAND.replace("Seat 1: fish77 folded on the Flop", "Seat 1: fish77 mucked [Ac 3d]");
+        assert_eq!(error(&shown), ImportError { line: 28, reason: "duplicate card" });
+        for k in ["6", "100000000000", "18446744073709551615"] {
+            let side = CASH_HAND.replace("$0.19 from pot", &format!("$0.19 from side pot-{}", k));
+            assert_eq!(error(&side), ImportError { line: 23, reason: "pot" });
+        }
+        assert_eq!(import("").len(), 0);
+        // more chips than the player has, or than a u64 holds
+        assert_eq!(error(&CASH_HAND.replace("fish77: calls $0.06", "fish77: calls $2.12")),
+            ImportError { line: 17, reason: "more than the stack" });
+        assert_eq!(error(&CASH_HAND.replace("fish77: calls $0.06", "fish77: calls 18446744073709551615")).reason,
+            "amount");
+        let uncalled = CASH_HAND.replace("Uncalled bet ($0.10)", "Uncalled bet ($184467440737095516.15)");
+        assert_eq!(error(&uncalled), ImportError { line: 23, reason: "amount" });
+        let short = CASH_HAND.replace("Seat 5: reg ($4.07", "Seat 5: reg ($0.01");
+        assert_eq!(error(&short), ImportError { line: 9, reason: "more than the stack" });
+
+        // parsed, but the engine does not agree
+        let short = imported(&CASH_HAND.replace("Hero collected $0.19", "Hero collected $0.18"));
+        assert_eq!(short.history.replay(NoLimit).unwrap_err(), crate::history::ReplayError::Mismatch("results"));
+    }
+
+    #[test]
+    fn uncalled_and_side_pots() {
+        let mut game = HoldemGame::new(GameConfig::new(1, 2), &[200, 50, 100, 0], 0, 3);
+        game.act(Action::AllIn).unwrap();
+        game.act(Action::AllIn).unwrap();
+        game.act(Action::AllIn).unwrap();
+        let history = HandHistory::from_game(&game, 1, seat_names(4));
+        let text = export(&history, &header()).unwrap();
+        assert!(text.contains("Seat 2: Seat 2 (small blind) showed"));
+        assert!(text.contains("Seat 1: raises 198 to 200 and is all-in\n"));
+        assert!(text.contains("Uncalled bet (100) returned to Seat 1\n"));
+        assert!(text.contains(" from main pot\n"));
+        assert!(text.contains(" from side pot-1\n"));
+        assert!(text.contains("Total pot 250 Main pot 150. Side pot-1 100. | Rake 0"));
+        assert!(!text.contains("Seat 4"));
+        // the board is dealt after the all-ins
+        let river = text.find("*** RIVER ***").unwrap();
+        assert!(text.find("Uncalled bet").unwrap() < river);
+
+        let mut folds = HoldemGame::new(GameConfig::new(1, 2), &[50, 100, 200], 0, 3);
+        folds.act(Action::Fold).unwrap();
+        folds.act(Action::Fold).unwrap();
+        let history = HandHistory::from_game(&folds, 2, seat_names(3));
+        let text = export(&history, &header()).unwrap();
+        assert!(text.contains("Uncalled bet (1) returned to Seat 3\nSeat 3 collected 2 from pot\n"));
+        assert!(text.contains("Seat 1: Seat 1 (button) folded before Flop (didn't bet)\n"));
+        assert!(text.contains("Seat 3: Seat 3 (big blind) collected (2)\n"));
+        assert!(!text.contains("Board"));
+        assert_eq!(export_all(&[history.clone(), history.clone()], &header()).unwrap().matches("PokerStars Hand").count(), 2);
+
+        // e.g. edited JSON
+        let error = |history: &HandHistory| export(history, &header()).unwrap_err().reason;
+        let mut short = history.clone();
+        short.stacks = vec![50, 100, 1];
+        assert_eq!(error(&short), "chips");
+        assert_eq!(export_all(&[history.clone(), short], &header()), Err(ExportError { reason: "chips" }));
+        let mut unknown = HandHistory::from_game(&game, 1, seat_names(4));
+        unknown.holes[1] = None;
+        assert_eq!(error(&unknown), "showdown");
+        let mut no_winner = HandHistory::from_game(&game, 1, seat_names(4));
+        no_winner.pots.pots[0].winners.clear();
+        assert_eq!(error(&no_winner), "pots");
+        let mut returned = history.clone();
+        returned.pots.pots[0].shares[0] = 0;
+        assert_eq!(error(&returned), "pots");
+        let mut late = history;
+        late.actions[0].0 = Street::Showdown;
+        assert_eq!(error(&late), "actions");
+    }
+}
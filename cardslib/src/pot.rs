@@ -0,0 +1,222 @@
+// Pot accounting: main pot, side pots and the split of each
+//
+// Pots are built from the chips each seat put in the hand. Every all-in
+// level of the live players closes a pot, so a player wins at most their
+// own contribution from each opponent:
+//   committed 50 (all-in), 100 (all-in), 200, 200
+//   => main pot 200 (seats 0 1 2 3), side pot 150 (1 2 3), side pot 200 (2 3)
+// Chips of folded players stay in the pots they reach. A pot which only
+// one seat put chips in is an uncalled bet and goes back to that seat.
+//
+// Each pot is split between the best eligible hands. Values only need Ord
+// (higher wins), so lowball games can pass core::cmp::Reverse values.
+// Odd chips are given one by one in odd_order, e.g. left_of_button().
+use core::fmt;
+use alloc::{vec, vec::Vec};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SidePot {
+    pub amount: u64,
+    // seats which can win the pot
+    pub eligible: Vec<usize>,
+    // only one seat put chips in
+    pub uncalled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PotAward {
+    pub pot: SidePot,
+    // seats in odd_order
+    pub winners: Vec<usize>,
+    // chips of each winner (same order as winners)
+    pub shares: Vec<u64>,
+    // chips which could not be split evenly
+    pub odd_chips: u64,
+}
+
+// the auditable result of a showdown (or of a hand won without one)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PotBreakdown {
+    // main pot first, then side pots in order
+    pub pots: Vec<PotAward>,
+    // chips won by each seat
+    pub payouts: Vec<u64>,
+}
+
+// committed: chips each seat put in the hand, live: not folded
+pub fn build_pots(committed: &[u64], live: &[bool]) -> Vec<SidePot> {
+    assert_eq!(committed.len(), live.len());
+    assert!(live.iter().any(|&l| l), "no live seat");
+
+    let mut levels: Vec<u64> = committed.iter().zip(live)
+        .filter(|(_, &l)| l)
+        .map(|(&c, _)| c)
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+    // chips of folded seats above the highest live level join the last pot
+    let top = *levels.last().unwrap();
+
+    let mut pots = Vec::new();
+    let mut prev = 0;
+    for &level in levels.iter() {
+        let upper = |c: u64| if level == top { c } else { c.min(level) };
+        let parts: Vec<u64> = committed.iter().map(|&c| upper(c) - c.min(prev)).collect();
+        let amount = parts.iter().sum();
+        if amount > 0 {
+            pots.push(SidePot {
+                amount,
+                eligible: (0..committed.len()).filter(|&i| live[i] && committed[i] >= level).collect(),
+                uncalled: parts.iter().filter(|&&p| p > 0).count() == 1,
+            });
+        }
+        prev = level;
+    }
+
+    pots
+}
+
+// seats from the left of the button to the button
+pub fn left_of_button(seats: usize, button: usize) -> Vec<usize> {
+    (1..=seats).map(|i| (button + i) % seats).collect()
+}
+
+// values: hand value of each seat (None = can not win, e.g. folded)
+// odd_order: all seats in the order of receiving odd chips
+pub fn award<V: Ord>(pots: &[SidePot], values: &[Option<V>], odd_order: &[usize]) -> PotBreakdown {
+    let mut breakdown = PotBreakdown {
+        pots: Vec::with_capacity(pots.len()),
+        payouts: vec![0; values.len()],
+    };
+    for pot in pots {
+        let best = pot.eligible.iter().filter_map(|&i| values[i].as_ref()).max();
+        let winners: Vec<usize> = odd_order.iter().copied()
+            .filter(|i| pot.eligible.contains(i) && values[*i].as_ref() == best)
+            .collect();
+        assert!(!winners.is_empty(), "no winner of the pot");
+
+        let count = winners.len() as u64;
+        let odd_chips = pot.amount % count;
+        let shares: Vec<u64> = (0..count)
+            .map(|k| pot.amount / count + if k < odd_chips { 1 } else { 0 })
+            .collect();
+        for (&i, &share) in winners.iter().zip(&shares) {
+            breakdown.payouts[i] += share;
+        }
+        breakdown.pots.push(PotAward { pot: pot.clone(), winners, shares, odd_chips });
+    }
+
+    breakdown
+}
+
+// one line per pot:
+//   main pot 200 [0 1 2 3]: seat 2 wins 200
+//   side pot 1 150 [1 2 3]: seats 1 2 split 75 75 (odd 0)
+//   uncalled 100: returned to seat 3
+impl fmt::Display for PotBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut side = 0;
+        for award in self.pots.iter() {
+            let pot = &award.pot;
+            if pot.uncalled {
+                writeln!(f, "uncalled {}: returned to seat {}", pot.amount, award.winners[0])?;
+                continue
+            }
+
+            if side == 0 {
+                write!(f, "main pot {} [", pot.amount)?;
+            }
+            else {
+                write!(f, "side pot {} {} [", side, pot.amount)?;
+            }
+            side += 1;
+            for (k, i) in pot.eligible.iter().enumerate() {
+                write!(f, "{}{}", if k == 0 { "" } else { " " }, i)?;
+            }
+            if award.winners.len() == 1 {
+                writeln!(f, "]: seat {} wins {}", award.winners[0], award.shares[0])?;
+            }
+            else {
+                write!(f, "]: seats")?;
+                for i in award.winners.iter() {
+                    write!(f, " {}", i)?;
+                }
+                write!(f, " split")?;
+                for share in award.shares.iter() {
+                    write!(f, " {}", share)?;
+                }
+                writeln!(f, " (odd {})", award.odd_chips)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cmp::Reverse;
+    use alloc::string::ToString;
+
+    #[test]
+    fn side_pots() {
+        let pots = build_pots(&[50, 100, 200, 200], &[true; 4]);
+        assert_eq!(pots, vec![
+            SidePot { amount: 200, eligible: vec![0, 1, 2, 3], uncalled: false },
+            SidePot { amount: 150, eligible: vec![1, 2, 3], uncalled: false },
+            SidePot { amount: 200, eligible: vec![2, 3], uncalled: false },
+        ]);
+
+        // the short stack has the best hand
+        let result = award(&pots, &[Some(9), Some(5), Some(7), Some(1)], &left_of_button(4, 3));
+        assert_eq!(result.payouts, vec![200, 0, 350, 0]);
+        assert_eq!(result.to_string(),
+            "main pot 200 [0 1 2 3]: seat 0 wins 200\n\
+             side pot 1 150 [1 2 3]: seat 2 wins 150\n\
+             side pot 2 200 [2 3]: seat 2 wins 200\n");
+    }
+
+    #[test]
+    fn folded_and_uncalled() {
+        // seat 0 folds after 30, seat 1 all-in 80, seat 2 bets 200
+        let pots = build_pots(&[30, 80, 200], &[false, true, true]);
+        assert_eq!(pots, vec![
+            SidePot { amount: 190, eligible: vec![1, 2], uncalled: false },
+            SidePot { amount: 120, eligible: vec![2], uncalled: true },
+        ]);
+        let result = award(&pots, &[None, Some(3), Some(2)], &left_of_button(3, 0));
+        assert_eq!(result.payouts, vec![0, 190, 120]);
+        assert!(result.to_string().ends_with("uncalled 120: returned to seat 2\n"));
+
+        // everyone folds to the big blind
+        let pots = build_pots(&[0, 1, 2], &[false, false, true]);
+        assert_eq!(pots, vec![SidePot { amount: 3, eligible: vec![2], uncalled: false }]);
+    }
+
+    #[test]
+    fn odd_chips() {
+        let pots = build_pots(&[25, 25, 25, 0], &[false, true, true, false]);
+        // the first winner left of the button gets the odd chip
+        let result = award(&pots, &[None, Some(4), Some(4), None], &left_of_button(4, 1));
+        assert_eq!(result.payouts, vec![0, 37, 38, 0]);
+        assert_eq!(result.pots[0].winners, vec![2, 1]);
+        assert_eq!(result.pots[0].odd_chips, 1);
+        assert_eq!(result.to_string(), "main pot 75 [1 2]: seats 2 1 split 38 37 (odd 1)\n");
+
+        // three way split of 100
+        let pots = build_pots(&[25; 4], &[true, true, true, false]);
+        let result = award(&pots, &[Some(1), Some(1), Some(1), None], &left_of_button(4, 2));
+        assert_eq!(result.payouts, vec![34, 33, 33, 0]);
+    }
+
+    #[test]
+    fn lowball() {
+        let pots = build_pots(&[10, 10], &[true; 2]);
+        let result = award(&pots, &[Some(Reverse(3)), Some(Reverse(5))], &[0, 1]);
+        assert_eq!(result.payouts, vec![20, 0]);
+    }
+}
@@ -0,0 +1,712 @@
+// Preflop hand classes and precomputed equity table
+//
+// 1326 starting hands collapse to 169 classes (13 pairs, 78 suited, 78 offsuit).
+// Class id is the position in the usual 13x13 grid (row * 13 + col):
+//   row/col 0 = A, 1 = K, ..., 12 = 2
+//   diagonal = pairs, upper right = suited, lower left = offsuit
+//
+// PreflopTable holds 169x169 heads-up equities (exhaustive) and
+// equities against N random hands. It is slow to generate (see
+// src/bin/preflop_table.rs), so generate once and load it with
+// PreflopTable::load() or PreflopTable::from_bytes(include_bytes!(...)).
+use core::fmt;
+use core::str::FromStr;
+use alloc::collections::BTreeMap;
+use alloc::{format, vec, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::{self, BufReader, BufWriter, Read, Write};
+#[cfg(feature = "std")]
+use std::path::Path;
+use crate::cards::{encode, decode, NUMBER_NUM, SUIT_NUM, NUMBER_CHARS};
+use crate::equity::{equity, equity_vs_random, HOLE_SIZE};
+use crate::index::HandIndexer;
+use crate::types::ParseError;
+
+pub const CLASS_NUM: usize = (NUMBER_NUM * NUMBER_NUM) as usize;
+// supported opponents for equities against random hands
+pub const MAX_OPPONENTS: usize = 9;
+
+const GRID: u32 = NUMBER_NUM;
+#[cfg(feature = "std")]
+const MAGIC: &[u8; 4] = b"DCPF";
+#[cfg(feature = "std")]
+const VERSION: u8 = 1;
+// equities are stored as u16 (1.0 = SCALE)
+const SCALE: f64 = 65535.0;
+
+// number => row/col in the grid (A first)
+fn grid_pos(number: u32) -> u32 {
+    GRID - 1 - number
+}
+
+// hole cards => class id
+pub fn class_of(hole: &[u32; HOLE_SIZE]) -> usize {
+    let (s1, n1) = decode(hole[0]);
+    let (s2, n2) = decode(hole[1]);
+    assert!(hole[0] != hole[1]);
+
+    let (high, low) = (n1.max(n2), n1.min(n2));
+    let (row, col) = if s1 == s2 {
+        (grid_pos(high), grid_pos(low))
+    }
+    else {
+        (grid_pos(low), grid_pos(high))
+    };
+
+    (row * GRID + col) as usize
+}
+
+// class id => (high number, low number, suited)
+pub fn class_numbers(class: usize) -> (u32, u32, bool) {
+    assert!(class < CLASS_NUM);
+
+    let row = class as u32 / GRID;
+    let col = class as u32 % GRID;
+    let (a, b) = (GRID - 1 - row, GRID - 1 - col);
+
+    (a.max(b), a.min(b), row < col)
+}
+
+// class id => "AA", "AKs", "72o", ...
+pub fn class_name(class: usize) -> String {
+    let (high, low, suited) = class_numbers(class);
+
+    let mut name = String::new();
+    name.push(NUMBER_CHARS[high as usize]);
+    name.push(NUMBER_CHARS[low as usize]);
+    if high != low {
+        name.push(if suited { 's' } else { 'o' });
+    }
+
+    name
+}
+
+// "AKs" => class id
+pub fn class_from_name(name: &str) -> Option<usize> {
+    let chars: Vec<char> = name.chars().collect();
+    let number = |c: char| NUMBER_CHARS.iter().position(|&x| x == c).map(|n| n as u32);
+
+    let high = number(*chars.first()?)?;
+    let low = number(*chars.get(1)?)?;
+    let suited = match (chars.get(2), high == low) {
+        (None, true) => false,
+        (Some('s'), false) => true,
+        (Some('o'), false) => false,
+        _ => return None,
+    };
+    if chars.len() > 3 {
+        return None
+    }
+    let (high, low) = (high.max(low), high.min(low));
+    let (row, col) = if suited {
+        (grid_pos(high), grid_pos(low))
+    }
+    else {
+        (grid_pos(low), grid_pos(high))
+    };
+
+    Some((row * GRID + col) as usize)
+}
+
+// all hole cards in the class (6, 4 or 12 combos)
+pub fn class_combos(class: usize) -> Vec<[u32; HOLE_SIZE]> {
+    let (high, low, suited) = class_numbers(class);
+
+    let mut result = Vec::new();
+    for s1 in 0..SUIT_NUM {
+        for s2 in 0..SUIT_NUM {
+            let ok = if high == low { s1 < s2 } else { (s1 == s2) == suited };
+            if ok {
+                result.push([encode(s1, high), encode(s2, low)]);
+            }
+        }
+    }
+
+    result
+}
+
+// exhaustive heads-up equity of class a against class b
+// Any combo of a is equivalent by suit symmetry, so the first one is used
+// against all non-conflicting combos of b. Suit isomorphic matchups are
+// calculated only once.
+pub fn class_equity(a: usize, b: usize) -> f64 {
+    class_equity_with(&HandIndexer::new(&[HOLE_SIZE, HOLE_SIZE]), a, b)
+}
+
+// class_equity() with an indexer of 2 + 2 cards (built once by the generator)
+fn class_equity_with(indexer: &HandIndexer, a: usize, b: usize) -> f64 {
+    let hero = class_combos(a)[0];
+
+    let mut cache: BTreeMap<u64, f64> = BTreeMap::new();
+    let mut sum = 0.0;
+    let mut count = 0;
+    for villain in class_combos(b) {
+        if villain.iter().any(|c| hero.contains(c)) {
+            continue
+        }
+        let key = indexer.index(&[hero[0], hero[1], villain[0], villain[1]]);
+        sum += *cache.entry(key)
+            .or_insert_with(|| equity(&[hero, villain], &[])[0]);
+        count += 1;
+    }
+
+    sum / count as f64
+}
+
+// Set of preflop classes
+//
+// Text notation (comma separated):
+//   "AA", "AKs", "AKo", "AK" (= AKs, AKo)
+//   "TT+" (TT..AA), "A9s+" (A9s..AKs), "K9o+" (K9o..KQo)
+//   "99-66", "A5s-A2s"
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Range {
+    classes: [bool; CLASS_NUM],
+}
+
+// (row, col) => class id
+fn grid_class(row: u32, col: u32) -> usize {
+    (row * GRID + col) as usize
+}
+
+// (high, low, suited) => class id
+fn class_id(high: u32, low: u32, suited: bool) -> usize {
+    if suited {
+        grid_class(grid_pos(high), grid_pos(low))
+    }
+    else {
+        grid_class(grid_pos(low), grid_pos(high))
+    }
+}
+
+impl Range {
+    pub fn new() -> Self {
+        Range { classes: [false; CLASS_NUM] }
+    }
+
+    pub fn full() -> Self {
+        Range { classes: [true; CLASS_NUM] }
+    }
+
+    pub fn contains(&self, class: usize) -> bool {
+        self.classes[class]
+    }
+
+    pub fn contains_hand(&self, hole: &[u32; HOLE_SIZE]) -> bool {
+        self.classes[class_of(hole)]
+    }
+
+    pub fn insert(&mut self, class: usize) {
+        self.classes[class] = true;
+    }
+
+    pub fn remove(&mut self, class: usize) {
+        self.classes[class] = false;
+    }
+
+    // class ids in grid order
+    pub fn classes(&self) -> Vec<usize> {
+        (0..CLASS_NUM).filter(|&c| self.classes[c]).collect()
+    }
+
+    // number of classes
+    pub fn len(&self) -> usize {
+        self.classes.iter().filter(|&&b| b).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // number of hole cards combos (1326 for full)
+    pub fn combos(&self) -> usize {
+        self.classes().iter().map(|&c| class_combos(c).len()).sum()
+    }
+
+    // 13 lines of the grid (A first), "." for classes not in the range
+    pub fn grid(&self) -> String {
+        let mut text = String::new();
+        for row in 0..GRID {
+            for col in 0..GRID {
+                let class = grid_class(row, col);
+                let cell = if self.classes[class] { class_name(class) } else { ".".into() };
+                text.push_str(&format!("{:<4}", cell));
+            }
+            text.truncate(text.trim_end().len());
+            text.push('\n');
+        }
+
+        text
+    }
+
+    // one token (without ",") => class ids
+    fn parse_token(token: &str) -> Option<Vec<usize>> {
+        let number = |c: char| NUMBER_CHARS.iter().position(|&x| x == c).map(|n| n as u32);
+        // "AKs" or "KAs" => (high, low, suited: None = both), like class_from_name()
+        let parse_one = |text: &str| -> Option<(u32, u32, Option<bool>)> {
+            let chars: Vec<char> = text.chars().collect();
+            let high = number(*chars.first()?)?;
+            let low = number(*chars.get(1)?)?;
+            let suited = match (chars.get(2), high == low) {
+                (None, _) => None,
+                (Some('s'), false) => Some(true),
+                (Some('o'), false) => Some(false),
+                _ => return None,
+            };
+            if chars.len() > 3 {
+                return None
+            }
+            Some((high.max(low), high.min(low), suited))
+        };
+        let expand = |high: u32, lows: &[u32], suited: Option<bool>| -> Vec<usize> {
+            lows.iter()
+                .flat_map(|&low| {
+                    if high == low {
+                        vec![class_id(high, low, false)]
+                    }
+                    else {
+                        match suited {
+                            Some(s) => vec![class_id(high, low, s)],
+                            None => vec![class_id(high, low, true), class_id(high, low, false)],
+                        }
+                    }
+                })
+                .collect()
+        };
+
+        if let Some(body) = token.strip_suffix('+') {
+            let (high, low, suited) = parse_one(body)?;
+            if high == low {
+                let pairs: Vec<usize> = (low..NUMBER_NUM)
+                    .map(|n| class_id(n, n, false))
+                    .collect();
+                return Some(pairs)
+            }
+            let lows: Vec<u32> = (low..high).collect();
+            return Some(expand(high, &lows, suited))
+        }
+        if let Some((from, to)) = token.split_once('-') {
+            let (h1, l1, s1) = parse_one(from)?;
+            let (h2, l2, s2) = parse_one(to)?;
+            if s1 != s2 {
+                return None
+            }
+            if h1 == l1 && h2 == l2 {
+                let pairs: Vec<usize> = (l1.min(l2)..=l1.max(l2))
+                    .map(|n| class_id(n, n, false))
+                    .collect();
+                return Some(pairs)
+            }
+            if h1 != h2 || h1 == l1 || h2 == l2 {
+                return None
+            }
+            let lows: Vec<u32> = (l1.min(l2)..=l1.max(l2)).collect();
+            return Some(expand(h1, &lows, s1))
+        }
+        let (high, low, suited) = parse_one(token)?;
+
+        Some(expand(high, &[low], suited))
+    }
+
+    // runs of consecutive classes (high, low, suited), the strongest first
+    fn runs(&self) -> Vec<Vec<(u32, u32, bool)>> {
+        let mut lines: Vec<Vec<(u32, u32, bool)>> = Vec::new();
+        lines.push((0..NUMBER_NUM).rev().map(|n| (n, n, false)).collect());
+        for high in (1..NUMBER_NUM).rev() {
+            for &suited in &[true, false] {
+                lines.push((0..high).rev().map(|low| (high, low, suited)).collect());
+            }
+        }
+
+        let mut result = Vec::new();
+        for line in lines {
+            let mut run: Vec<(u32, u32, bool)> = Vec::new();
+            for (i, &(high, low, suited)) in line.iter().enumerate() {
+                if self.classes[class_id(high, low, suited)] {
+                    run.push((high, low, suited));
+                }
+                if !run.is_empty() && (i + 1 == line.len() || !self.classes[class_id(high, low, suited)]) {
+                    result.push(core::mem::take(&mut run));
+                }
+            }
+        }
+
+        result
+    }
+}
+
+impl Default for Range {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = |&(high, low, suited): &(u32, u32, bool)| class_name(class_id(high, low, suited));
+
+        let mut tokens = Vec::new();
+        for run in self.runs() {
+            let (first, last) = (run[0], run[run.len() - 1]);
+            let top = if first.0 == first.1 { first.0 == NUMBER_NUM - 1 } else { first.1 + 1 == first.0 };
+            let token = if run.len() == 1 {
+                name(&first)
+            }
+            else if top {
+                format!("{}+", name(&last))
+            }
+            else {
+                format!("{}-{}", name(&first), name(&last))
+            };
+            tokens.push(token);
+        }
+
+        f.write_str(&tokens.join(","))
+    }
+}
+
+impl FromStr for Range {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut range = Range::new();
+        let mut pos = 0;
+        for token in s.split(',') {
+            let trimmed = token.trim();
+            if !trimmed.is_empty() {
+                let offset = pos + token.len() - token.trim_start().len();
+                let classes = Self::parse_token(trimmed).ok_or(ParseError { pos: offset })?;
+                for class in classes {
+                    range.insert(class);
+                }
+            }
+            pos += token.len() + 1;
+        }
+
+        Ok(range)
+    }
+}
+
+// "AA,AKs,KQo"
+#[cfg(feature = "serde")]
+impl serde::Serialize for Range {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Range {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(|e| D::Error::custom(format!("invalid range: {}", e)))
+    }
+}
+
+pub struct PreflopTable {
+    opponents: usize,
+    // [a * CLASS_NUM + b]: a vs b
+    matrix: Vec<u16>,
+    // [(n - 1) * CLASS_NUM + a]: a vs n random hands
+    vs_random: Vec<u16>,
+}
+
+fn to_fixed(equity: f64) -> u16 {
+    // no f64::round() in core
+    (equity * SCALE + 0.5) as u16
+}
+
+fn from_fixed(value: u16) -> f64 {
+    value as f64 / SCALE
+}
+
+#[cfg(feature = "std")]
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+impl PreflopTable {
+    // opponents: vs 1..=opponents random hands
+    // vs 1 is exact (weighted by non-conflicting combos), others are
+    // Monte Carlo with trials per class.
+    // progress is called with the number of finished matrix rows.
+    pub fn generate<F>(opponents: usize, trials: u32, seed: u64, mut progress: F) -> Self
+        where F: FnMut(usize)
+    {
+        assert!((1..=MAX_OPPONENTS).contains(&opponents));
+
+        let indexer = HandIndexer::new(&[HOLE_SIZE, HOLE_SIZE]);
+        let mut eq = vec![0.0; CLASS_NUM * CLASS_NUM];
+        for a in 0..CLASS_NUM {
+            for b in a..CLASS_NUM {
+                let value = class_equity_with(&indexer, a, b);
+                eq[a * CLASS_NUM + b] = value;
+                eq[b * CLASS_NUM + a] = 1.0 - value;
+            }
+            progress(a + 1);
+        }
+
+        let mut vs_random = vec![0.0; CLASS_NUM * opponents];
+        for a in 0..CLASS_NUM {
+            let hero = class_combos(a)[0];
+            // vs 1 random hand: weighted by combos which do not conflict with hero
+            let mut sum = 0.0;
+            let mut count = 0;
+            for b in 0..CLASS_NUM {
+                let combos = class_combos(b).iter()
+                    .filter(|v| v.iter().all(|c| !hero.contains(c)))
+                    .count();
+                sum += eq[a * CLASS_NUM + b] * combos as f64;
+                count += combos;
+            }
+            vs_random[a] = sum / count as f64;
+            for n in 2..=opponents {
+                let value = equity_vs_random(&hero, &[], n, trials, seed ^ (a * 16 + n) as u64);
+                vs_random[(n - 1) * CLASS_NUM + a] = value;
+            }
+        }
+
+        Self {
+            opponents,
+            matrix: eq.into_iter().map(to_fixed).collect(),
+            vs_random: vs_random.into_iter().map(to_fixed).collect(),
+        }
+    }
+
+    pub fn opponents(&self) -> usize {
+        self.opponents
+    }
+
+    // class a vs class b
+    pub fn equity(&self, a: usize, b: usize) -> f64 {
+        assert!(a < CLASS_NUM && b < CLASS_NUM);
+
+        from_fixed(self.matrix[a * CLASS_NUM + b])
+    }
+
+    // class a vs n random hands
+    pub fn equity_vs_random(&self, a: usize, n: usize) -> f64 {
+        assert!(a < CLASS_NUM);
+        assert!((1..=self.opponents).contains(&n));
+
+        from_fixed(self.vs_random[(n - 1) * CLASS_NUM + a])
+    }
+
+    // hole cards vs hole cards (class approximation, card removal is averaged)
+    pub fn equity_hands(&self, hero: &[u32; HOLE_SIZE], villain: &[u32; HOLE_SIZE]) -> f64 {
+        self.equity(class_of(hero), class_of(villain))
+    }
+
+    // Binary format (little endian):
+    //   "DCPF", version: u8, opponents: u8,
+    //   matrix: u16 * 169 * 169, vs_random: u16 * 169 * opponents
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&[VERSION, self.opponents as u8])?;
+        for &value in self.matrix.iter().chain(self.vs_random.iter()) {
+            w.write_all(&value.to_le_bytes())?;
+        }
+
+        w.flush()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn read_from<R: Read>(mut r: R) -> io::Result<Self> {
+        let mut header = [0u8; 6];
+        r.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid_data("not a preflop table"))
+        }
+        if header[4] != VERSION {
+            return Err(invalid_data("unsupported version"))
+        }
+        let opponents = header[5] as usize;
+        if !(1..=MAX_OPPONENTS).contains(&opponents) {
+            return Err(invalid_data("invalid opponents"))
+        }
+
+        let mut read_values = |len: usize| -> io::Result<Vec<u16>> {
+            let mut buf = vec![0u8; len * 2];
+            r.read_exact(&mut buf)?;
+            Ok(buf.chunks(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect())
+        };
+        let matrix = read_values(CLASS_NUM * CLASS_NUM)?;
+        let vs_random = read_values(CLASS_NUM * opponents)?;
+
+        Ok(Self { opponents, matrix, vs_random })
+    }
+
+    // for include_bytes!()
+    #[cfg(feature = "std")]
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        Self::read_from(bytes)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    // CSV for humans and spreadsheets
+    //   class,vs_AA,vs_AKs,...,vs_random_1,...
+    #[cfg(feature = "std")]
+    pub fn write_csv<W: Write>(&self, mut w: W) -> io::Result<()> {
+        write!(w, "class")?;
+        for b in 0..CLASS_NUM {
+            write!(w, ",vs_{}", class_name(b))?;
+        }
+        for n in 1..=self.opponents {
+            write!(w, ",vs_random_{}", n)?;
+        }
+        writeln!(w)?;
+
+        for a in 0..CLASS_NUM {
+            write!(w, "{}", class_name(a))?;
+            for b in 0..CLASS_NUM {
+                write!(w, ",{:.4}", self.equity(a, b))?;
+            }
+            for n in 1..=self.opponents {
+                write!(w, ",{:.4}", self.equity_vs_random(a, n))?;
+            }
+            writeln!(w)?;
+        }
+
+        w.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards;
+
+    #[test]
+    fn classes() {
+        let mut count = vec![0; CLASS_NUM];
+        for hand in cards::all_case(&[], 2) {
+            let class = class_of(&[hand[0], hand[1]]);
+            count[class] += 1;
+            assert_eq!(class_of(&[hand[1], hand[0]]), class);
+        }
+        for (class, &c) in count.iter().enumerate() {
+            assert_eq!(class_combos(class).len(), c);
+            assert_eq!(class_from_name(&class_name(class)), Some(class));
+        }
+        assert_eq!(count.iter().sum::<usize>(), 1326);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(class_name(0), "AA");
+        assert_eq!(class_name(1), "AKs");
+        assert_eq!(class_name(GRID as usize), "AKo");
+        assert_eq!(class_name(CLASS_NUM - 1), "22");
+        assert_eq!(class_from_name("KAs"), class_from_name("AKs"));
+        assert_eq!(class_from_name("AA"), Some(0));
+        assert_eq!(class_from_name("AAs"), None);
+        assert_eq!(class_from_name("AK"), None);
+        assert_eq!(class_from_name("AKx"), None);
+        assert_eq!(class_from_name("AKo "), None);
+    }
+
+    #[test]
+    fn range_parse() {
+        let range: Range = "TT+, AKs, A5s-A2s, KQ".parse().unwrap();
+        assert_eq!(range.len(), 5 + 1 + 4 + 2);
+        assert_eq!(range.combos(), 5 * 6 + 4 + 4 * 4 + 4 + 12);
+        assert!(range.contains(class_from_name("JJ").unwrap()));
+        assert!(!range.contains(class_from_name("99").unwrap()));
+        assert!(range.contains(class_from_name("A3s").unwrap()));
+        assert!(!range.contains(class_from_name("A3o").unwrap()));
+        assert!(range.contains(class_from_name("KQo").unwrap()));
+
+        let suited: Range = "K9s+".parse().unwrap();
+        assert_eq!(suited.to_string(), "K9s+");
+        assert_eq!(suited.len(), 4);
+        let pairs: Range = "55-88".parse().unwrap();
+        assert_eq!(pairs.to_string(), "88-55");
+
+        assert_eq!("AA,XX".parse::<Range>(), Err(ParseError { pos: 3 }));
+        assert!("AKs-KQs".parse::<Range>().is_err());
+        assert!("AAs".parse::<Range>().is_err());
+        assert_eq!("KAs, 9K+".parse::<Range>(), "AKs, K9+".parse::<Range>());
+        assert_eq!("".parse::<Range>(), Ok(Range::new()));
+    }
+
+    #[test]
+    fn range_display() {
+        let range: Range = "22+,A2s+,K9o+,T8s,65s".parse().unwrap();
+        assert_eq!(range.to_string(), "22+,A2s+,K9o+,T8s,65s");
+        assert_eq!(range.to_string().parse::<Range>().unwrap(), range);
+        assert_eq!(Range::full().combos(), 1326);
+        assert_eq!(Range::full().to_string().parse::<Range>().unwrap(), Range::full());
+
+        let mut range = Range::new();
+        range.insert(class_from_name("AA").unwrap());
+        range.insert(class_from_name("QQ").unwrap());
+        range.insert(class_from_name("KJo").unwrap());
+        assert_eq!(range.to_string(), "AA,QQ,KJo");
+
+        let grid = range.grid();
+        assert_eq!(grid.lines().count(), 13);
+        assert_eq!(grid.lines().next(), Some("AA  .   .   .   .   .   .   .   .   .   .   .   ."));
+        assert!(grid.lines().nth(2).unwrap().starts_with(".   .   QQ  ."));
+        assert!(grid.lines().nth(3).unwrap().starts_with(".   KJo .   ."));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn range_serde() {
+        let range: Range = "QQ+,AK".parse().unwrap();
+        let json = serde_json::to_string(&range).unwrap();
+        assert_eq!(json, r#""QQ+,AKs,AKo""#);
+        assert_eq!(serde_json::from_str::<Range>(&json).unwrap(), range);
+        assert!(serde_json::from_str::<Range>(r#""QQ+,AX""#).is_err());
+    }
+
+    #[test]
+    fn aa_vs_kk() {
+        let aa = class_from_name("AA").unwrap();
+        let kk = class_from_name("KK").unwrap();
+        let value = class_equity(aa, kk);
+        assert!(value > 0.815 && value < 0.825);
+    }
+
+    #[test]
+    fn table_io() {
+        let opponents = 2;
+        let table = PreflopTable {
+            opponents,
+            matrix: (0..CLASS_NUM * CLASS_NUM).map(|i| i as u16).collect(),
+            vs_random: (0..CLASS_NUM * opponents).map(|i| i as u16 * 3).collect(),
+        };
+        let mut buf = Vec::new();
+        table.write_to(&mut buf).unwrap();
+        assert_eq!(buf.len(), 6 + 2 * CLASS_NUM * (CLASS_NUM + opponents));
+
+        let loaded = PreflopTable::from_bytes(&buf).unwrap();
+        assert_eq!(loaded.opponents(), opponents);
+        assert_eq!(loaded.matrix, table.matrix);
+        assert_eq!(loaded.vs_random, table.vs_random);
+        assert_eq!(loaded.equity(0, 1), 1.0 / SCALE);
+        assert_eq!(loaded.equity_vs_random(1, 2), (CLASS_NUM + 1) as f64 * 3.0 / SCALE);
+
+        assert!(PreflopTable::from_bytes(&buf[..100]).is_err());
+        assert!(PreflopTable::from_bytes(b"XXXX\x01\x01").is_err());
+
+        let mut csv = Vec::new();
+        loaded.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), CLASS_NUM + 1);
+        assert!(csv.starts_with("class,vs_AA,vs_AKs,"));
+    }
+}
@@ -0,0 +1,204 @@
+// Heads-up push/fold equilibrium
+//
+// The small blind goes all-in or folds, the big blind calls or folds.
+// Strategies are per preflop class, weighted by the combos each pair of
+// classes has without common cards. Fictitious play: each iteration both
+// players best respond to the average strategy of the other, and the
+// averages converge to the equilibrium.
+//
+// Amounts are in big blinds. stack: effective stack at the start of the
+// hand (including the blind and ante), ante: paid by each player.
+// Class equities come from the preflop table (see PushFold::from_table()).
+use core::fmt;
+use alloc::{vec, vec::Vec};
+use crate::preflop::{class_combos, PreflopTable, Range, CLASS_NUM};
+
+const SMALL_BLIND: f64 = 0.5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushFold {
+    pub stack: f64,
+    pub ante: f64,
+    // probability to push / call of each class
+    pub push: Vec<f64>,
+    pub call: Vec<f64>,
+}
+
+// [a * CLASS_NUM + b]: combo pairs of classes a and b without common cards
+fn combo_weights() -> Vec<f64> {
+    let combos: Vec<Vec<u64>> = (0..CLASS_NUM)
+        .map(|c| class_combos(c).iter().map(|h| 1 << h[0] | 1 << h[1]).collect())
+        .collect();
+    let mut weights = vec![0.0; CLASS_NUM * CLASS_NUM];
+    for a in 0..CLASS_NUM {
+        for b in a..CLASS_NUM {
+            let count = combos[a].iter()
+                .map(|x| combos[b].iter().filter(|&&y| x & y == 0).count())
+                .sum::<usize>() as f64;
+            weights[a * CLASS_NUM + b] = count;
+            weights[b * CLASS_NUM + a] = count;
+        }
+    }
+
+    weights
+}
+
+impl PushFold {
+    // equity(a, b): all-in equity of class a against class b
+    pub fn solve<F>(stack: f64, ante: f64, iterations: u32, equity: F) -> Self
+        where F: Fn(usize, usize) -> f64
+    {
+        assert!(stack > 1.0 + ante && ante >= 0.0);
+
+        let weights = combo_weights();
+        let equities: Vec<f64> = (0..CLASS_NUM * CLASS_NUM)
+            .map(|k| equity(k / CLASS_NUM, k % CLASS_NUM))
+            .collect();
+        // the blind and ante of the big blind
+        let win_blinds = 1.0 + ante;
+        let fold_small = -(SMALL_BLIND + ante);
+        // net of an all-in with the equity
+        let showdown = |e: f64| e * 2.0 * stack - stack;
+
+        let mut push = vec![1.0; CLASS_NUM];
+        let mut call = vec![1.0; CLASS_NUM];
+        for t in 1..=iterations {
+            let step = 1.0 / (t + 1) as f64;
+            // the big blind against the pushing range
+            let call_br: Vec<bool> = (0..CLASS_NUM)
+                .map(|b| {
+                    let (mut ev_call, mut ev_fold) = (0.0, 0.0);
+                    for a in 0..CLASS_NUM {
+                        let w = weights[a * CLASS_NUM + b] * push[a];
+                        ev_call += w * showdown(equities[b * CLASS_NUM + a]);
+                        ev_fold -= w * win_blinds;
+                    }
+                    ev_call > ev_fold
+                })
+                .collect();
+            // the small blind against the calling range
+            let push_br: Vec<bool> = (0..CLASS_NUM)
+                .map(|a| {
+                    let (mut ev, mut total) = (0.0, 0.0);
+                    for b in 0..CLASS_NUM {
+                        let w = weights[a * CLASS_NUM + b];
+                        let c = call[b];
+                        ev += w * (c * showdown(equities[a * CLASS_NUM + b]) + (1.0 - c) * win_blinds);
+                        total += w;
+                    }
+                    ev / total > fold_small
+                })
+                .collect();
+
+            for (p, br) in push.iter_mut().zip(push_br) {
+                *p += (br as u8 as f64 - *p) * step;
+            }
+            for (c, br) in call.iter_mut().zip(call_br) {
+                *c += (br as u8 as f64 - *c) * step;
+            }
+        }
+
+        PushFold { stack, ante, push, call }
+    }
+
+    // with the heads-up equities of the table
+    pub fn from_table(table: &PreflopTable, stack: f64, ante: f64, iterations: u32) -> Self {
+        Self::solve(stack, ante, iterations, |a, b| table.equity(a, b))
+    }
+
+    // classes pushed at least half of the time
+    pub fn push_range(&self) -> Range {
+        to_range(&self.push)
+    }
+
+    pub fn call_range(&self) -> Range {
+        to_range(&self.call)
+    }
+}
+
+fn to_range(freq: &[f64]) -> Range {
+    let mut range = Range::new();
+    for (class, &f) in freq.iter().enumerate() {
+        if f >= 0.5 {
+            range.insert(class);
+        }
+    }
+
+    range
+}
+
+// push and call ranges as grids, then as range text
+impl fmt::Display for PushFold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (push, call) = (self.push_range(), self.call_range());
+        writeln!(f, "stack {}bb, ante {}bb", self.stack, self.ante)?;
+        writeln!(f, "push ({} combos):", push.combos())?;
+        write!(f, "{}", push.grid())?;
+        writeln!(f, "call ({} combos):", call.combos())?;
+        write!(f, "{}", call.grid())?;
+        writeln!(f, "push: {}", push)?;
+        writeln!(f, "call: {}", call)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preflop::{class_from_name, class_numbers};
+
+    // a rough equity: the sum of the numbers, pairs and suits count
+    fn equity(a: usize, b: usize) -> f64 {
+        let strength = |c: usize| {
+            let (high, low, suited) = class_numbers(c);
+            let pair = if high == low { 12.0 } else { 0.0 };
+            (high * 2 + low) as f64 + pair + if suited { 1.0 } else { 0.0 }
+        };
+        0.5 + (strength(a) - strength(b)) / 120.0
+    }
+
+    fn class(name: &str) -> usize {
+        class_from_name(name).unwrap()
+    }
+
+    #[test]
+    fn weights() {
+        let weights = combo_weights();
+        let w = |a: &str, b: &str| weights[class(a) * CLASS_NUM + class(b)];
+        assert_eq!(w("AA", "AA"), 6.0);
+        assert_eq!(w("AA", "KK"), 36.0);
+        assert_eq!(w("AA", "AKo"), 6.0 * 6.0);
+        assert_eq!(w("AKs", "AKo"), 4.0 * 6.0);
+        // each KQs blocks the 3 AKo with its king
+        assert_eq!(w("AKo", "KQs"), 12.0 * 4.0 - 4.0 * 3.0);
+    }
+
+    #[test]
+    fn stack_depth() {
+        let short = PushFold::solve(2.0, 0.0, 200, equity);
+        let deep = PushFold::solve(20.0, 0.0, 200, equity);
+        // pushes almost anything with 2bb
+        assert!(short.push_range().len() > 150);
+        let (push, call) = (deep.push_range(), deep.call_range());
+        for name in ["AA", "KK", "AKs"] {
+            assert!(push.contains(class(name)));
+            assert!(call.contains(class(name)));
+        }
+        assert!(!push.contains(class("32o")));
+        assert!(push.len() < short.push_range().len());
+        // calls are tighter than pushes
+        assert!(call.len() < push.len());
+        // antes widen the ranges
+        let ante = PushFold::solve(20.0, 0.5, 200, equity);
+        assert!(ante.push_range().len() >= push.len());
+        assert!(ante.call_range().len() > call.len());
+    }
+
+    #[test]
+    fn output() {
+        let chart = PushFold::solve(10.0, 0.0, 100, equity);
+        let text = chart.to_string();
+        assert_eq!(text.lines().count(), 1 + 2 * 14 + 2);
+        let push = text.lines().find_map(|l| l.strip_prefix("push: ")).unwrap();
+        assert_eq!(push.parse::<Range>().unwrap(), chart.push_range());
+    }
+}
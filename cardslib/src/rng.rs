@@ -0,0 +1,100 @@
+// Small deterministic random number generator (SplitMix64)
+//
+// Not for cryptographic use. The same seed always gives the same sequence
+// on every platform, so simulations and deals can be reproduced.
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use crate::cards::CARDS_NUM;
+
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        z ^ (z >> 31)
+    }
+
+    // uniform in 0..n
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0);
+
+        // reject the biased tail
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % n
+            }
+        }
+    }
+
+    // uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Fisher-Yates
+    pub fn shuffle<T>(&mut self, list: &mut [T]) {
+        for i in (1..list.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            list.swap(i, j);
+        }
+    }
+
+    // all cards except for used, shuffled
+    #[cfg(feature = "alloc")]
+    pub fn deck(&mut self, used: &[u32]) -> Vec<u32> {
+        let mut deck: Vec<u32> = (0..CARDS_NUM).filter(|c| !used.contains(c)).collect();
+        self.shuffle(&mut deck);
+
+        deck
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        let mut rng1 = Rng::new(1);
+        let mut rng2 = Rng::new(1);
+        let mut rng3 = Rng::new(2);
+        let seq1: Vec<u64> = (0..10).map(|_| rng1.next_u64()).collect();
+        let seq2: Vec<u64> = (0..10).map(|_| rng2.next_u64()).collect();
+        let seq3: Vec<u64> = (0..10).map(|_| rng3.next_u64()).collect();
+        assert_eq!(seq1, seq2);
+        assert_ne!(seq1, seq3);
+    }
+
+    #[test]
+    fn below() {
+        let mut rng = Rng::new(0);
+        let mut count = [0; 6];
+        for _ in 0..6000 {
+            count[rng.below(6) as usize] += 1;
+        }
+        assert!(count.iter().all(|&c| c > 900 && c < 1100));
+    }
+
+    #[test]
+    fn deck() {
+        let mut rng = Rng::new(0);
+        let mut deck = rng.deck(&[0, 51]);
+        assert_eq!(deck.len(), 50);
+        deck.sort_unstable();
+        assert_eq!(deck, (1..51).collect::<Vec<u32>>());
+    }
+}
@@ -0,0 +1,465 @@
+// Player statistics from hand histories
+//
+// StatsAggregator adds finished hands one at a time (a live table) or
+// many at once (imported files) and keeps the counters of each player by
+// name:
+//   VPIP   put chips in preflop voluntarily, % of hands
+//   PFR    raised preflop, % of hands
+//   3-bet  raised the first raise preflop, % of the chances
+//   AF     (bets + raises) / calls after the flop
+//   WTSD   went to the showdown, % of the flops seen
+//   W$SD   won a pot at the showdown, % of the showdowns
+//   c-bet  the last preflop raiser bet the flop, % of the chances
+// and the hands played and chips won in each position.
+use core::fmt;
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+use crate::history::HandHistory;
+use crate::holdem::{Action, Post, Street};
+use crate::pot::left_of_button;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Position {
+    SmallBlind,
+    BigBlind,
+    Early,
+    Middle,
+    Cutoff,
+    Button,
+}
+
+pub const POSITION_NUM: usize = 6;
+
+pub const POSITIONS: [Position; POSITION_NUM] = [
+    Position::SmallBlind,
+    Position::BigBlind,
+    Position::Early,
+    Position::Middle,
+    Position::Cutoff,
+    Position::Button,
+];
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Position::SmallBlind => "SB",
+            Position::BigBlind => "BB",
+            Position::Early => "EP",
+            Position::Middle => "MP",
+            Position::Cutoff => "CO",
+            Position::Button => "BTN",
+        };
+        f.pad(name)
+    }
+}
+
+// position of each seat dealt in (None = empty seat)
+// Heads-up the button is the small blind, counted as the button. The
+// seats between the big blind and the cutoff are early, then middle.
+pub fn positions(history: &HandHistory) -> Vec<Option<Position>> {
+    let n = history.stacks.len();
+    let order: Vec<usize> = left_of_button(n, history.button).into_iter()
+        .filter(|&i| history.stacks[i] > 0)
+        .collect();
+    let m = order.len();
+    let early = m.saturating_sub(4).div_ceil(2);
+
+    let mut result = vec![None; n];
+    for (k, &i) in order.iter().enumerate() {
+        result[i] = Some(match k {
+            _ if i == history.button => Position::Button,
+            _ if m == 2 => Position::BigBlind,
+            0 => Position::SmallBlind,
+            1 => Position::BigBlind,
+            _ if k == m - 2 => Position::Cutoff,
+            _ if k - 2 < early => Position::Early,
+            _ => Position::Middle,
+        });
+    }
+
+    result
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerStats {
+    pub hands: u64,
+    pub vpip_hands: u64,
+    pub pfr_hands: u64,
+    pub three_bet_chances: u64,
+    pub three_bets: u64,
+    // actions after the flop
+    pub bets_raises: u64,
+    pub calls: u64,
+    pub flops: u64,
+    pub showdowns: u64,
+    // won at least a part of a pot at the showdown
+    pub showdown_wins: u64,
+    pub cbet_chances: u64,
+    pub cbets: u64,
+    // chips won (negative = lost), and in big blinds of each hand
+    pub net: i64,
+    pub net_bb: f64,
+    // [Position as usize]: (hands, chips won)
+    pub by_position: [(u64, i64); POSITION_NUM],
+}
+
+fn percent(count: u64, total: u64) -> f64 {
+    if total == 0 { 0.0 } else { count as f64 * 100.0 / total as f64 }
+}
+
+impl PlayerStats {
+    pub fn vpip(&self) -> f64 {
+        percent(self.vpip_hands, self.hands)
+    }
+
+    pub fn pfr(&self) -> f64 {
+        percent(self.pfr_hands, self.hands)
+    }
+
+    pub fn three_bet(&self) -> f64 {
+        percent(self.three_bets, self.three_bet_chances)
+    }
+
+    // None without any call
+    pub fn aggression_factor(&self) -> Option<f64> {
+        if self.calls == 0 { None } else { Some(self.bets_raises as f64 / self.calls as f64) }
+    }
+
+    pub fn wtsd(&self) -> f64 {
+        percent(self.showdowns, self.flops)
+    }
+
+    pub fn wsd(&self) -> f64 {
+        percent(self.showdown_wins, self.showdowns)
+    }
+
+    pub fn cbet(&self) -> f64 {
+        percent(self.cbets, self.cbet_chances)
+    }
+
+    pub fn bb_per_100(&self) -> f64 {
+        if self.hands == 0 { 0.0 } else { self.net_bb * 100.0 / self.hands as f64 }
+    }
+}
+
+// what each seat did in one hand
+#[derive(Debug, Clone, Copy, Default)]
+struct HandFlags {
+    vpip: bool,
+    pfr: bool,
+    three_bet_chance: bool,
+    three_bet: bool,
+    cbet_chance: bool,
+    cbet: bool,
+    folded: bool,
+    bets_raises: u64,
+    calls: u64,
+}
+
+fn hand_flags(h: &HandHistory) -> Vec<HandFlags> {
+    let n = h.stacks.len();
+    let mut flags = vec![HandFlags::default(); n];
+    let mut stacks = h.stacks.clone();
+    let mut bets = vec![0u64; n];
+    let mut current = 0;
+    // saturating: hands from files may put in more than the stack
+    for &(i, post) in h.posts.iter() {
+        match post {
+            Post::Ante(a) | Post::DeadSmallBlind(a) => stacks[i] = stacks[i].saturating_sub(a),
+            Post::SmallBlind(a) | Post::BigBlind(a) | Post::Straddle(a) | Post::BringIn(a) => {
+                stacks[i] = stacks[i].saturating_sub(a);
+                bets[i] = bets[i].saturating_add(a);
+                current = current.max(bets[i]);
+            }
+        }
+    }
+
+    let mut street = Street::Preflop;
+    // raises preflop, the last one raising
+    let mut raises = 0;
+    let mut aggressor = None;
+    let mut flop_bet = false;
+    for &(s, i, action) in h.actions.iter() {
+        if s != street {
+            street = s;
+            bets.iter_mut().for_each(|b| *b = 0);
+            current = 0;
+        }
+        let to = match action {
+            Action::Call => current.min(bets[i].saturating_add(stacks[i])),
+            Action::Bet(to) | Action::Raise(to) => to,
+            Action::AllIn => bets[i].saturating_add(stacks[i]),
+            Action::Fold | Action::Check => bets[i],
+        };
+        let aggressive = to > current;
+        let f = &mut flags[i];
+        match street {
+            Street::Preflop => {
+                if raises == 1 && !f.three_bet_chance {
+                    f.three_bet_chance = true;
+                    f.three_bet = aggressive;
+                }
+                if aggressive {
+                    f.pfr = true;
+                    raises += 1;
+                    aggressor = Some(i);
+                }
+                f.vpip |= to > bets[i];
+            }
+            _ => {
+                if street == Street::Flop && aggressor == Some(i) && !flop_bet && !f.cbet_chance {
+                    f.cbet_chance = true;
+                    f.cbet = aggressive;
+                }
+                if aggressive {
+                    f.bets_raises += 1;
+                    flop_bet |= street == Street::Flop;
+                }
+                else if to > bets[i] {
+                    f.calls += 1;
+                }
+            }
+        }
+        f.folded |= action == Action::Fold;
+        stacks[i] = stacks[i].saturating_sub(to.saturating_sub(bets[i]));
+        bets[i] = to;
+        current = current.max(to);
+    }
+
+    flags
+}
+
+// the stats of every player seen, by name
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatsAggregator {
+    players: BTreeMap<String, PlayerStats>,
+    hands: u64,
+}
+
+impl StatsAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // batch of hands, e.g. imported from files
+    pub fn from_histories<'a, I>(histories: I) -> Self
+        where I: IntoIterator<Item = &'a HandHistory>
+    {
+        let mut stats = Self::new();
+        for history in histories {
+            stats.add(history);
+        }
+
+        stats
+    }
+
+    // a finished hand, seats without a name are not counted
+    pub fn add(&mut self, history: &HandHistory) {
+        let h = history;
+        let flags = hand_flags(h);
+        let positions = positions(h);
+        let saw_flop = h.board.len() >= 3;
+        let showdown = !h.showdown.is_empty();
+        let big_blind = h.config.big_blind.max(1) as f64;
+
+        for (i, f) in flags.iter().enumerate() {
+            let position = match positions[i] {
+                Some(position) if !h.players[i].is_empty() => position,
+                _ => continue,
+            };
+            let stats = self.players.entry(h.players[i].clone()).or_default();
+            stats.hands += 1;
+            stats.vpip_hands += f.vpip as u64;
+            stats.pfr_hands += f.pfr as u64;
+            stats.three_bet_chances += f.three_bet_chance as u64;
+            stats.three_bets += f.three_bet as u64;
+            stats.cbet_chances += f.cbet_chance as u64;
+            stats.cbets += f.cbet as u64;
+            stats.bets_raises += f.bets_raises;
+            stats.calls += f.calls;
+
+            let folded_preflop = f.folded && !h.actions.iter().any(|a| a.1 == i && a.0 != Street::Preflop);
+            if saw_flop && !folded_preflop {
+                stats.flops += 1;
+                if showdown && !f.folded {
+                    stats.showdowns += 1;
+                    let won = h.pots.pots.iter().any(|a| !a.pot.uncalled && a.winners.contains(&i));
+                    stats.showdown_wins += won as u64;
+                }
+            }
+
+            stats.net += h.net[i];
+            stats.net_bb += h.net[i] as f64 / big_blind;
+            let seat = &mut stats.by_position[position as usize];
+            seat.0 += 1;
+            seat.1 += h.net[i];
+        }
+        self.hands += 1;
+    }
+
+    // hands added
+    pub fn hands(&self) -> u64 {
+        self.hands
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PlayerStats> {
+        self.players.get(name)
+    }
+
+    // by name
+    pub fn players(&self) -> impl Iterator<Item = (&str, &PlayerStats)> {
+        self.players.iter().map(|(name, stats)| (name.as_str(), stats))
+    }
+}
+
+// a row per player, then the chips won in each position:
+//   player  hands  VPIP   PFR  3bet    AF  WTSD  W$SD  cbet  bb/100
+impl fmt::Display for StatsAggregator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.players.keys().map(|n| n.chars().count()).max().unwrap_or(0).max(6);
+        writeln!(f, "{:width$} {:>6} {:>5} {:>5} {:>5} {:>5} {:>5} {:>5} {:>5} {:>8}",
+            "player", "hands", "VPIP", "PFR", "3bet", "AF", "WTSD", "W$SD", "cbet", "bb/100", width = width)?;
+        for (name, s) in self.players.iter() {
+            let af = s.aggression_factor().map_or(String::from("-"), |af| alloc::format!("{:.1}", af));
+            writeln!(f, "{:width$} {:>6} {:>5.1} {:>5.1} {:>5.1} {:>5} {:>5.1} {:>5.1} {:>5.1} {:>+8.2}",
+                name, s.hands, s.vpip(), s.pfr(), s.three_bet(), af, s.wtsd(), s.wsd(), s.cbet(), s.bb_per_100(),
+                width = width)?;
+        }
+        write!(f, "{:width$}", "chips", width = width)?;
+        for position in POSITIONS.iter() {
+            write!(f, " {:>8}", position)?;
+        }
+        writeln!(f)?;
+        for (name, s) in self.players.iter() {
+            write!(f, "{:width$}", name, width = width)?;
+            for &(_, net) in s.by_position.iter() {
+                write!(f, " {:>+8}", net)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::betting::NoLimit;
+    use crate::cards::list_from_text;
+    use crate::history::seat_names;
+    use crate::holdem::{GameConfig, HoldemGame};
+    use crate::player::{play, RandomPlayer};
+
+    // seats 0 SB, 1 BB, 2 UTG, 3 BTN; the button has aces
+    fn raised_hand() -> HandHistory {
+        let deck = list_from_text("3c Kc Qh As 8d Kd Td Ah 2c 7d 9h Js 4c").unwrap();
+        let mut game = HoldemGame::with_deck(GameConfig::new(1, 2), NoLimit, &[100; 4], 3, &deck);
+        let actions = [
+            Action::Raise(6), Action::Raise(18), Action::Fold, Action::Call, Action::Call,
+            // flop: the button c-bets
+            Action::Check, Action::Check, Action::Bet(20), Action::Call, Action::Fold,
+            Action::Check, Action::Check,
+            Action::Bet(30), Action::Call,
+        ];
+        for action in actions.iter() {
+            game.act(*action).unwrap();
+        }
+        assert!(game.is_finished());
+        HandHistory::from_game(&game, 1, seat_names(4))
+    }
+
+    #[test]
+    fn seat_positions() {
+        let mut history = raised_hand();
+        use Position::*;
+        assert_eq!(positions(&history), vec![Some(SmallBlind), Some(BigBlind), Some(Cutoff), Some(Button)]);
+
+        history.stacks = vec![100; 9];
+        history.button = 8;
+        let expected = [SmallBlind, BigBlind, Early, Early, Early, Middle, Middle, Cutoff, Button];
+        assert_eq!(positions(&history), expected.iter().map(|&p| Some(p)).collect::<Vec<_>>());
+
+        history.stacks = vec![100, 0, 100];
+        history.button = 2;
+        assert_eq!(positions(&history), vec![Some(BigBlind), None, Some(Button)]);
+    }
+
+    #[test]
+    fn counters() {
+        let stats = StatsAggregator::from_histories(&[raised_hand()]);
+        let get = |i: usize| stats.get(&seat_names(4)[i]).unwrap();
+        let (sb, bb, utg, button) = (get(0), get(1), get(2), get(3));
+
+        assert_eq!((sb.vpip_hands, sb.pfr_hands, sb.flops), (0, 0, 0));
+        assert_eq!((utg.vpip_hands, utg.pfr_hands, utg.three_bet_chances), (1, 1, 0));
+        // only the button faced the first raise before it was re-raised
+        assert_eq!((button.three_bet_chances, button.three_bets), (1, 1));
+        assert_eq!((sb.three_bet_chances, bb.three_bet_chances), (0, 0));
+        assert_eq!((bb.vpip_hands, bb.pfr_hands), (1, 0));
+
+        assert_eq!((button.cbet_chances, button.cbets), (1, 1));
+        assert_eq!(utg.cbet_chances, 0);
+        assert_eq!(button.aggression_factor(), Some(1.0));
+        assert_eq!(bb.aggression_factor(), Some(1.0));
+        assert_eq!(utg.aggression_factor(), None);
+
+        assert_eq!((button.flops, button.showdowns, button.showdown_wins), (1, 1, 1));
+        assert_eq!((bb.flops, bb.showdowns, bb.showdown_wins), (1, 1, 0));
+        assert_eq!((utg.flops, utg.showdowns), (1, 0));
+        assert_eq!(utg.wtsd(), 0.0);
+        assert_eq!(button.wsd(), 100.0);
+
+        // pot 1 + 3 * 18 + 2 * 20 + 2 * 30
+        assert_eq!(button.net, 155 - 68);
+        assert_eq!(button.by_position[Position::Button as usize], (1, button.net));
+        assert_eq!(sb.by_position[Position::SmallBlind as usize], (1, -1));
+        assert_eq!(bb.bb_per_100(), -34.0 * 100.0);
+    }
+
+    #[test]
+    fn incremental() {
+        let hands: Vec<HandHistory> = (0..40)
+            .map(|seed| {
+                let mut game = HoldemGame::new(GameConfig::new(1, 2), &[100, 60, 0, 150], seed as usize % 4 / 3 * 3, seed);
+                let mut players: Vec<RandomPlayer> = (0..4).map(|i| RandomPlayer::new(seed * 4 + i)).collect();
+                play(&mut game, &mut players).unwrap();
+                HandHistory::from_game(&game, seed, seat_names(4))
+            })
+            .collect();
+
+        let batch = StatsAggregator::from_histories(&hands);
+        let mut live = StatsAggregator::new();
+        for hand in hands.iter() {
+            live.add(hand);
+        }
+        assert_eq!(live.to_string(), batch.to_string());
+        assert_eq!(batch.hands(), 40);
+        assert!(batch.get("Seat 3").is_none());
+
+        let players: Vec<(&str, &PlayerStats)> = batch.players().collect();
+        assert_eq!(players.len(), 3);
+        assert_eq!(players.iter().map(|p| p.1.net).sum::<i64>(), 0);
+        for (_, s) in players.iter() {
+            assert_eq!(s.hands, 40);
+            assert!(s.pfr_hands <= s.vpip_hands && s.showdowns <= s.flops);
+            assert_eq!(s.by_position.iter().map(|p| p.0).sum::<u64>(), 40);
+            assert_eq!(s.by_position.iter().map(|p| p.1).sum::<i64>(), s.net);
+        }
+        // header, players, positions header, players
+        assert_eq!(batch.to_string().lines().count(), 2 + 2 * 3);
+    }
+
+    #[test]
+    fn inconsistent_hand() {
+        // a file or JSON may have more chips put in than the stacks
+        let mut history = raised_hand();
+        history.stacks = vec![1, 10, 5, 100];
+        let stats = StatsAggregator::from_histories(&[history]);
+        let button = stats.get("Seat 4").unwrap();
+        assert_eq!((button.hands, button.pfr_hands, button.cbets), (1, 1, 1));
+        assert_eq!(stats.get("Seat 2").unwrap().vpip_hands, 1);
+    }
+}
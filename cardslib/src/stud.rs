@@ -0,0 +1,423 @@
+// Seven-card stud game state machine
+//
+// Third street: two down cards and one up card each, antes, and the
+// lowest upcard (by number, then suit: see cards::card_order()) posts the
+// bring-in. Fourth to sixth street: one up card each, seventh street: one
+// down card. From fourth street the best showing hand acts first.
+// The best five of seven cards wins (odd chips to the highest card by suit).
+//
+// With 8 players the deck may not have a seventh street card for everyone;
+// then one community card is dealt face up for all.
+//
+// Fixed-limit by default: small bet on third and fourth street, big bet
+// from fifth street. Betting is the same as holdem (see table::Table).
+use alloc::{vec, vec::Vec};
+use crate::betting::{BettingStructure, FixedLimit};
+use crate::cards::{calc_best, calc_best_cards, card_order, decode, Rank, NUMBER_NUM};
+use crate::rng::Rng;
+use crate::table::{Table, Post, Progress, Action, GameError, LegalActions};
+use crate::types::HandValue;
+
+pub const MAX_STUD_PLAYERS: usize = 8;
+// cards of each player at the showdown
+pub const STUD_CARDS: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StudConfig {
+    pub ante: u64,
+    // at most small_bet
+    pub bring_in: u64,
+    pub small_bet: u64,
+    pub big_bet: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StudStreet {
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Showdown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StudCard {
+    pub code: u32,
+    // face up: seen by everyone
+    pub up: bool,
+}
+
+// the value of up cards (1..=4) to decide the first seat to act
+// Straights and flashes do not count.
+pub fn showing_value(up: &[u32]) -> (Rank, u32) {
+    assert!(!up.is_empty() && up.len() <= 4);
+
+    let mut count = [0u32; NUMBER_NUM as usize];
+    for &code in up {
+        count[decode(code).1 as usize] += 1;
+    }
+    // numbers by count, then by number (descending)
+    let mut numbers: Vec<(u32, u32)> = (0..NUMBER_NUM)
+        .filter(|&n| count[n as usize] > 0)
+        .map(|n| (count[n as usize], n))
+        .collect();
+    numbers.sort_unstable_by(|a, b| b.cmp(a));
+
+    let pairs = numbers.iter().filter(|&&(c, _)| c == 2).count();
+    let rank = match numbers[0].0 {
+        4 => Rank::Quads,
+        3 => Rank::Trips,
+        2 if pairs == 2 => Rank::TwoPair,
+        2 => Rank::OnePair,
+        _ => Rank::HighCard,
+    };
+    // 4 bits per number, as calc_hand()
+    let order = numbers.iter().fold(0, |order, &(_, n)| order << 4 | n);
+
+    (rank, order)
+}
+
+#[derive(Debug, Clone)]
+pub struct StudGame<B = FixedLimit> {
+    config: StudConfig,
+    table: Table<B>,
+    // empty if the seat is not dealt in
+    cards: Vec<Vec<StudCard>>,
+    community: Option<u32>,
+    deck: Vec<u32>,
+    street: StudStreet,
+    bring_in: usize,
+    history: Vec<(StudStreet, usize, Action)>,
+}
+
+impl StudGame {
+    // fixed-limit
+    // stacks: chips of each seat (0 = empty seat), dealer: occupied seat
+    // (the deal starts left of the dealer)
+    pub fn new(config: StudConfig, stacks: &[u64], dealer: usize, seed: u64) -> Self {
+        let limit = FixedLimit { big_bet: config.big_bet, cap: 4, ..FixedLimit::new(config.small_bet) };
+        Self::with_structure(config, limit, stacks, dealer, seed)
+    }
+}
+
+impl<B: BettingStructure> StudGame<B> {
+    pub fn with_structure(
+        config: StudConfig, structure: B, stacks: &[u64], dealer: usize, seed: u64) -> Self
+    {
+        assert!(stacks.iter().filter(|&&s| s > 0).count() <= MAX_STUD_PLAYERS);
+        assert!(config.bring_in > 0 && config.bring_in <= config.small_bet);
+        assert!(config.small_bet <= config.big_bet);
+
+        let mut game = StudGame {
+            config,
+            table: Table::new(structure, stacks, dealer),
+            cards: vec![Vec::new(); stacks.len()],
+            community: None,
+            deck: Rng::new(seed).deck(&[]),
+            street: StudStreet::Third,
+            bring_in: 0,
+            history: Vec::new(),
+        };
+        let order = game.table.dealt_seats();
+        for up in [false, false, true] {
+            for &i in order.iter() {
+                game.deal(i, up);
+            }
+        }
+        for &i in order.iter() {
+            game.table.post(i, Post::Ante(config.ante));
+        }
+
+        // the lowest upcard brings in, completing is to the small bet
+        game.bring_in = *order.iter().min_by_key(|&&i| card_order(game.cards[i][2].code)).unwrap();
+        let complete = if config.bring_in < config.small_bet {
+            config.small_bet - config.bring_in
+        }
+        else {
+            config.small_bet
+        };
+        if game.table.post_bring_in(game.bring_in, config.bring_in, complete) == Progress::RoundOver {
+            game.next_street();
+        }
+
+        game
+    }
+
+    pub fn config(&self) -> StudConfig {
+        self.config
+    }
+
+    // chips, pots and forced bets
+    pub fn table(&self) -> &Table<B> {
+        &self.table
+    }
+
+    pub fn street(&self) -> StudStreet {
+        self.street
+    }
+
+    // the seat which posted the bring-in
+    pub fn bring_in(&self) -> usize {
+        self.bring_in
+    }
+
+    // all cards of the seat in the order dealt (empty if not dealt in)
+    pub fn cards(&self, seat: usize) -> &[StudCard] {
+        &self.cards[seat]
+    }
+
+    // the shared seventh street card, if the deck ran short
+    pub fn community(&self) -> Option<u32> {
+        self.community
+    }
+
+    // up cards of the seat
+    pub fn up_cards(&self, seat: usize) -> Vec<u32> {
+        self.cards[seat].iter().filter(|c| c.up).map(|c| c.code).collect()
+    }
+
+    // cards of the seat as the viewer sees them (None = face down)
+    // viewer None is an observer. Hands at the showdown are open.
+    pub fn view(&self, seat: usize, viewer: Option<usize>) -> Vec<Option<u32>> {
+        let open = viewer == Some(seat)
+            || (self.street == StudStreet::Showdown && self.table.seats()[seat].in_hand());
+        self.cards[seat].iter()
+            .map(|c| if c.up || open { Some(c.code) } else { None })
+            .collect()
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        self.table.to_act()
+    }
+
+    pub fn legal_actions(&self) -> LegalActions {
+        self.table.legal_actions()
+    }
+
+    // (street, seat, action) in order, without antes and the bring-in
+    pub fn history(&self) -> &[(StudStreet, usize, Action)] {
+        &self.history
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.table.is_finished()
+    }
+
+    // chips won by each seat (all zero until finished)
+    pub fn payouts(&self) -> &[u64] {
+        self.table.payouts()
+    }
+
+    pub fn act(&mut self, action: Action) -> Result<(), GameError> {
+        let seat = self.table.to_act().ok_or(GameError::Finished)?;
+        let progress = self.table.act(action)?;
+        self.history.push((self.street, seat, action));
+        if progress == Progress::RoundOver {
+            self.next_street();
+        }
+
+        Ok(())
+    }
+
+    fn deal(&mut self, seat: usize, up: bool) {
+        let code = self.deck.pop().unwrap();
+        self.cards[seat].push(StudCard { code, up });
+    }
+
+    // all cards of the seat including the community card
+    fn codes(&self, seat: usize) -> Vec<u32> {
+        self.cards[seat].iter().map(|c| c.code).chain(self.community).collect()
+    }
+
+    fn next_street(&mut self) {
+        loop {
+            if self.street == StudStreet::Seventh {
+                self.showdown();
+                return
+            }
+
+            self.street = match self.street {
+                StudStreet::Third => StudStreet::Fourth,
+                StudStreet::Fourth => StudStreet::Fifth,
+                StudStreet::Fifth => StudStreet::Sixth,
+                _ => StudStreet::Seventh,
+            };
+            let seats = self.table.seats();
+            let live: Vec<usize> = self.table.dealt_seats().into_iter()
+                .filter(|&i| seats[i].in_hand())
+                .collect();
+            if self.street == StudStreet::Seventh && self.deck.len() < live.len() {
+                self.community = self.deck.pop();
+            }
+            else {
+                for &i in live.iter() {
+                    self.deal(i, self.street != StudStreet::Seventh);
+                }
+            }
+
+            // the best showing hand acts first, the first from the dealer on ties
+            let first = *live.iter()
+                .rev()
+                .max_by_key(|&&i| showing_value(&self.up_cards(i)))
+                .unwrap();
+            let big = self.street >= StudStreet::Fifth;
+            let bet = if big { self.config.big_bet } else { self.config.small_bet };
+            // no betting when at most one player has chips
+            if self.table.new_round(bet, big) && self.table.start_from(first) == Progress::Next {
+                return
+            }
+        }
+    }
+
+    fn showdown(&mut self) {
+        self.street = StudStreet::Showdown;
+        let seats = self.table.seats();
+        let values: Vec<Option<HandValue>> = (0..self.cards.len())
+            .map(|i| seats[i].in_hand().then(|| calc_best(&self.codes(i)).into()))
+            .collect();
+        // the highest card by suit in the best five first
+        let high = |i: usize| -> Option<u32> {
+            seats[i].in_hand().then(|| {
+                calc_best_cards(&self.codes(i)).iter().map(|&c| card_order(c)).max().unwrap()
+            })
+        };
+        let mut odd_order: Vec<usize> = (0..self.cards.len()).collect();
+        odd_order.sort_by_key(|&i| core::cmp::Reverse(high(i)));
+        self.table.finish(&values, &odd_order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::list_from_text;
+
+    const CONFIG: StudConfig = StudConfig { ante: 1, bring_in: 2, small_bet: 5, big_bet: 10 };
+
+    fn showing(text: &str) -> (Rank, u32) {
+        showing_value(&list_from_text(text).unwrap())
+    }
+
+    fn call_down<B: BettingStructure>(game: &mut StudGame<B>) {
+        while !game.is_finished() {
+            let action = if game.legal_actions().check { Action::Check } else { Action::Call };
+            game.act(action).unwrap();
+        }
+    }
+
+    #[test]
+    fn showing_order() {
+        assert!(showing("2c 2d") > showing("Ac Kd"));
+        assert!(showing("Ac Kd") > showing("Ac Qd"));
+        assert!(showing("3c 3d 2h 2s") > showing("Ac Ad Kh"));
+        assert!(showing("4c 4d 4h") > showing("Ac Ad Kh Kd"));
+        assert_eq!(showing("5c 6c 7c 8c").0, Rank::HighCard);
+        assert_eq!(showing("9c"), showing("9s"));
+    }
+
+    #[test]
+    fn bring_in() {
+        let mut game = StudGame::new(CONFIG, &[100, 100, 100, 100], 0, 1);
+        let lowest = (0..4).min_by_key(|&i| card_order(game.up_cards(i)[0])).unwrap();
+        assert_eq!(game.bring_in(), lowest);
+        assert_eq!(game.table().posts().last(), Some(&(lowest, Post::BringIn(2))));
+        assert_eq!(game.table().pot(), 4 + 2);
+        assert_eq!(game.to_act(), Some((lowest + 1) % 4));
+        // call the bring-in or complete to the small bet
+        let legal = game.legal_actions();
+        assert_eq!(legal.call, Some(2));
+        assert_eq!(legal.raise, Some((5, 5)));
+        game.act(Action::Raise(5)).unwrap();
+        assert_eq!(game.legal_actions().raise, Some((10, 10)));
+        game.act(Action::Call).unwrap();
+        game.act(Action::Call).unwrap();
+        // the bring-in faces the completion
+        assert_eq!(game.to_act(), Some(lowest));
+        game.act(Action::Call).unwrap();
+        assert_eq!(game.street(), StudStreet::Fourth);
+        assert_eq!(game.table().pot(), 4 + 20);
+    }
+
+    #[test]
+    fn full_bring_in() {
+        // a bring-in of the small bet can only be raised, and the raise reopens
+        let config = StudConfig { bring_in: 5, ..CONFIG };
+        let mut game = StudGame::new(config, &[100, 100, 100], 0, 1);
+        let lowest = game.bring_in();
+        assert_eq!(game.legal_actions().raise, Some((10, 10)));
+        game.act(Action::Raise(10)).unwrap();
+        game.act(Action::Call).unwrap();
+        assert_eq!(game.to_act(), Some(lowest));
+        assert_eq!(game.legal_actions().raise, Some((15, 15)));
+        game.act(Action::Raise(15)).unwrap();
+        assert_eq!(game.legal_actions().raise, Some((20, 20)));
+    }
+
+    #[test]
+    fn no_option_for_bring_in() {
+        let mut game = StudGame::new(CONFIG, &[100, 100, 100], 0, 2);
+        game.act(Action::Call).unwrap();
+        game.act(Action::Call).unwrap();
+        assert_eq!(game.street(), StudStreet::Fourth);
+
+        // the best showing hand acts first, betting the small bet
+        let best = (0..3).map(|i| showing_value(&game.up_cards(i))).max().unwrap();
+        let first = game.to_act().unwrap();
+        assert_eq!(showing_value(&game.up_cards(first)), best);
+        assert_eq!(game.legal_actions().bet, Some((5, 5)));
+    }
+
+    #[test]
+    fn showdown() {
+        let mut game = StudGame::new(CONFIG, &[100, 100, 0, 100], 0, 3);
+        call_down(&mut game);
+        assert_eq!(game.street(), StudStreet::Showdown);
+        assert!(game.cards(2).is_empty());
+        for i in [0, 1, 3] {
+            assert_eq!(game.cards(i).len(), STUD_CARDS);
+            assert_eq!(game.up_cards(i).len(), 4);
+        }
+        assert_eq!(game.payouts().iter().sum::<u64>(), 3 + 6);
+
+        let value = |i: usize| calc_best(&game.codes(i));
+        let best = [0, 1, 3].iter().map(|&i| value(i)).max().unwrap();
+        for i in [0, 1, 3] {
+            assert_eq!(game.payouts()[i] > 0, value(i) == best);
+        }
+    }
+
+    #[test]
+    fn visibility() {
+        let mut game = StudGame::new(CONFIG, &[100, 100, 100], 0, 4);
+        let view = game.view(1, Some(0));
+        assert_eq!(view.iter().filter(|c| c.is_none()).count(), 2);
+        assert_eq!(view[2], Some(game.up_cards(1)[0]));
+        assert!(game.view(1, Some(1)).iter().all(|c| c.is_some()));
+        assert_eq!(game.view(1, None), view);
+
+        // seat 0 folds, the others go to the showdown
+        while game.to_act() != Some(0) {
+            game.act(Action::Call).unwrap();
+        }
+        game.act(Action::Fold).unwrap();
+        call_down(&mut game);
+        assert!(game.view(1, Some(0)).iter().all(|c| c.is_some()));
+        assert_eq!(game.view(0, Some(1)).iter().filter(|c| c.is_none()).count(), 2);
+    }
+
+    #[test]
+    fn community_card() {
+        let stacks = [100; MAX_STUD_PLAYERS];
+        let mut game = StudGame::new(CONFIG, &stacks, 0, 5);
+        call_down(&mut game);
+        // 52 - 8 * 6 = 4 cards left for the seventh street
+        let community = game.community().unwrap();
+        for i in 0..MAX_STUD_PLAYERS {
+            assert_eq!(game.cards(i).len(), 6);
+            assert!(!game.codes(i)[..6].contains(&community));
+            assert_eq!(game.codes(i).len(), STUD_CARDS);
+        }
+        assert_eq!(game.payouts().iter().sum::<u64>(), 8 + 8 * 2);
+    }
+}
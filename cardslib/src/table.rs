@@ -0,0 +1,553 @@
+// Chips, forced bets and betting rounds shared by the game engines
+//
+// A Table keeps the chips of each seat and runs betting rounds with a
+// BettingStructure. The engines (holdem, draw, ...) deal the cards, start
+// each round from their own first seat and evaluate the showdown.
+//
+// Amounts of Bet and Raise are "to" amounts: the total bet of the seat
+// in the current round, not the chips added by the action.
+use core::fmt;
+use alloc::{vec, vec::Vec};
+use crate::betting::{BettingStructure, BetState};
+use crate::pot::{build_pots, award, left_of_button, PotBreakdown};
+
+pub const MAX_SEATS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    Fold,
+    Check,
+    Call,
+    // to amount, when nobody has bet in the round
+    Bet(u64),
+    // to amount, when facing a bet
+    Raise(u64),
+    // the whole stack, as a call, bet or raise
+    AllIn,
+}
+
+// antes are dead money: in the pot, but not a part of the bet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ante {
+    #[default]
+    None,
+    // every seat dealt in
+    Each(u64),
+    // the big blind pays for the table (after the blind if short)
+    BigBlind(u64),
+}
+
+// forced bets of the blind games
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameConfig {
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub ante: Ante,
+    // live blind of the seat left of the big blind (0 = none),
+    // usually 2 big blinds; not with 2 players
+    pub straddle: u64,
+}
+
+impl GameConfig {
+    // blinds only
+    pub const fn new(small_blind: u64, big_blind: u64) -> Self {
+        GameConfig { small_blind, big_blind, ante: Ante::None, straddle: 0 }
+    }
+
+    // blinds the table can post: small <= big, a straddle above the big blind
+    pub const fn is_valid(&self) -> bool {
+        self.small_blind <= self.big_blind && self.big_blind > 0
+            && (self.straddle == 0 || self.straddle > self.big_blind)
+    }
+}
+
+// forced bets, with the chips actually put (short stacks post less)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Post {
+    Ante(u64),
+    SmallBlind(u64),
+    BigBlind(u64),
+    Straddle(u64),
+    // the small blind of a returning seat (not a part of the bet)
+    DeadSmallBlind(u64),
+    // stud: the lowest upcard opens
+    BringIn(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seat {
+    // chips behind
+    pub stack: u64,
+    // chips put in the current round
+    pub bet: u64,
+    // chips put in the hand (including bet)
+    pub committed: u64,
+    // has cards in the hand (empty seats are not)
+    pub dealt: bool,
+    pub folded: bool,
+}
+
+impl Seat {
+    // dealt in and not folded
+    pub fn in_hand(&self) -> bool {
+        self.dealt && !self.folded
+    }
+
+    pub fn is_all_in(&self) -> bool {
+        self.in_hand() && self.stack == 0
+    }
+
+    pub(crate) fn can_act(&self) -> bool {
+        self.in_hand() && self.stack > 0
+    }
+}
+
+// what the seat to act can do
+// Bet and Raise ranges are min..=max of the to amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LegalActions {
+    pub fold: bool,
+    pub check: bool,
+    // chips to add
+    pub call: Option<u64>,
+    pub bet: Option<(u64, u64)>,
+    pub raise: Option<(u64, u64)>,
+    // to amount
+    pub all_in: Option<u64>,
+}
+
+impl LegalActions {
+    pub fn contains(&self, action: Action) -> bool {
+        let within = |range: Option<(u64, u64)>, to: u64| {
+            range.is_some_and(|(min, max)| (min..=max).contains(&to))
+        };
+        match action {
+            Action::Fold => self.fold,
+            Action::Check => self.check,
+            Action::Call => self.call.is_some(),
+            Action::Bet(to) => within(self.bet, to),
+            Action::Raise(to) => within(self.raise, to),
+            Action::AllIn => self.all_in.is_some(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    // the hand is over
+    Finished,
+    // not in legal_actions()
+    IllegalAction(Action),
+    // missed blinds are posted before the first action, by a seat
+    // dealt in which has not posted a blind
+    InvalidPost(usize),
+    // not drawing now, or discards which are not distinct cards of the hand
+    InvalidDraw,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::Finished => write!(f, "the hand is finished"),
+            GameError::IllegalAction(action) => write!(f, "illegal action: {:?}", action),
+            GameError::InvalidPost(seat) => write!(f, "seat {} can not post blinds now", seat),
+            GameError::InvalidDraw => write!(f, "invalid draw"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GameError {}
+
+// what happened after an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Progress {
+    // to_act() is the next seat
+    Next,
+    // nobody needs to act in the round
+    RoundOver,
+    // everyone else folded (the table is finished)
+    HandOver,
+}
+
+#[derive(Debug, Clone)]
+pub struct Table<B> {
+    structure: B,
+    seats: Vec<Seat>,
+    button: usize,
+    to_act: Option<usize>,
+    // the bet to call in the current round
+    current_bet: u64,
+    // the size of the last full raise (at least the big blind)
+    min_raise: u64,
+    // full bets and raises in the current round
+    bets: u32,
+    // the round uses the big bet (fixed-limit)
+    big_bet: bool,
+    // current_bet when the seat acted last in the round (None = not yet)
+    acted: Vec<Option<u64>>,
+    // any action has been taken in the hand
+    started: bool,
+    posts: Vec<(usize, Post)>,
+    breakdown: PotBreakdown,
+    finished: bool,
+}
+
+impl<B: BettingStructure> Table<B> {
+    // stacks: chips of each seat (0 = empty seat, not dealt in)
+    pub(crate) fn new(structure: B, stacks: &[u64], button: usize) -> Self {
+        assert!((2..=MAX_SEATS).contains(&stacks.len()));
+        assert!(stacks.iter().filter(|&&s| s > 0).count() >= 2, "2 players are required");
+        assert!(stacks[button] > 0, "button must be occupied");
+
+        Table {
+            structure,
+            seats: stacks.iter().map(|&stack| Seat {
+                stack, bet: 0, committed: 0, dealt: stack > 0, folded: false,
+            }).collect(),
+            button,
+            to_act: None,
+            current_bet: 0,
+            min_raise: 0,
+            bets: 0,
+            big_bet: false,
+            acted: vec![None; stacks.len()],
+            started: false,
+            posts: Vec::new(),
+            breakdown: PotBreakdown { pots: Vec::new(), payouts: vec![0; stacks.len()] },
+            finished: false,
+        }
+    }
+
+    pub fn structure(&self) -> &B {
+        &self.structure
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    pub fn button(&self) -> usize {
+        self.button
+    }
+
+    // None when nobody has to bet (the hand is finished, or cards are drawn)
+    pub fn to_act(&self) -> Option<usize> {
+        self.to_act
+    }
+
+    pub fn current_bet(&self) -> u64 {
+        self.current_bet
+    }
+
+    // all chips put in the hand
+    pub fn pot(&self) -> u64 {
+        self.seats.iter().map(|s| s.committed).sum()
+    }
+
+    // (seat, forced bet) in order of posting
+    pub fn posts(&self) -> &[(usize, Post)] {
+        &self.posts
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // chips won by each seat (all zero until finished)
+    pub fn payouts(&self) -> &[u64] {
+        &self.breakdown.payouts
+    }
+
+    // main and side pots and who won them (empty until finished)
+    pub fn pot_breakdown(&self) -> &PotBreakdown {
+        &self.breakdown
+    }
+
+    // payout - committed of each seat
+    pub fn net_results(&self) -> Vec<i64> {
+        self.seats.iter().zip(&self.breakdown.payouts)
+            .map(|(seat, &payout)| payout as i64 - seat.committed as i64)
+            .collect()
+    }
+
+    // the next seat after from (cyclic) which satisfies pred
+    pub(crate) fn next_seat(&self, from: usize, pred: impl Fn(&Seat) -> bool) -> Option<usize> {
+        let n = self.seats.len();
+        (1..=n).map(|i| (from + i) % n).find(|&i| pred(&self.seats[i]))
+    }
+
+    // seats dealt in, starting left of the button
+    pub(crate) fn dealt_seats(&self) -> Vec<usize> {
+        left_of_button(self.seats.len(), self.button).into_iter()
+            .filter(|&i| self.seats[i].dealt)
+            .collect()
+    }
+
+    // put chips up to the stack => chips put
+    fn put(&mut self, i: usize, amount: u64) -> u64 {
+        let seat = &mut self.seats[i];
+        let amount = amount.min(seat.stack);
+        seat.stack -= amount;
+        seat.bet += amount;
+        seat.committed += amount;
+
+        amount
+    }
+
+    // put chips in the pot, but not in the bet
+    fn put_dead(&mut self, i: usize, amount: u64) -> u64 {
+        let seat = &mut self.seats[i];
+        let amount = amount.min(seat.stack);
+        seat.stack -= amount;
+        seat.committed += amount;
+
+        amount
+    }
+
+    // antes and dead blinds are dead, the others live
+    pub(crate) fn post(&mut self, i: usize, post: Post) {
+        let post = match post {
+            Post::Ante(amount) => Post::Ante(self.put_dead(i, amount)),
+            Post::DeadSmallBlind(amount) => Post::DeadSmallBlind(self.put_dead(i, amount)),
+            Post::SmallBlind(amount) => Post::SmallBlind(self.put(i, amount)),
+            Post::BigBlind(amount) => Post::BigBlind(self.put(i, amount)),
+            Post::Straddle(amount) => Post::Straddle(self.put(i, amount)),
+            Post::BringIn(amount) => Post::BringIn(self.put(i, amount)),
+        };
+        self.posts.push((i, post));
+    }
+
+    // heads-up: the button posts the small blind
+    fn blind_seats(&self) -> (usize, usize) {
+        let dealt = self.seats.iter().filter(|s| s.dealt).count();
+        let sb = if dealt == 2 {
+            self.button
+        }
+        else {
+            self.next_seat(self.button, |s| s.dealt).unwrap()
+        };
+        let bb = self.next_seat(sb, |s| s.dealt).unwrap();
+
+        (sb, bb)
+    }
+
+    // antes, blinds and straddle, then the first seat to act
+    pub(crate) fn post_blinds(&mut self, config: &GameConfig) -> Progress {
+        assert!(config.is_valid(), "invalid blinds");
+
+        let (sb, bb) = self.blind_seats();
+        if let Ante::Each(ante) = config.ante {
+            for i in 0..self.seats.len() {
+                if self.seats[i].dealt {
+                    self.post(i, Post::Ante(ante));
+                }
+            }
+        }
+        self.post(sb, Post::SmallBlind(config.small_blind));
+        self.post(bb, Post::BigBlind(config.big_blind));
+        if let Ante::BigBlind(ante) = config.ante {
+            self.post(bb, Post::Ante(ante));
+        }
+        self.current_bet = config.big_blind;
+        self.min_raise = config.big_blind;
+        self.bets = 1;
+        let mut last = bb;
+
+        let dealt = self.seats.iter().filter(|s| s.dealt).count();
+        if config.straddle > 0 && dealt > 2 {
+            let utg = self.next_seat(bb, |s| s.dealt).unwrap();
+            self.post(utg, Post::Straddle(config.straddle));
+            // the straddle is the biggest blind: raises are by at least its size
+            self.current_bet = config.straddle;
+            self.min_raise = config.straddle;
+            self.bets = 2;
+            last = utg;
+        }
+
+        self.start_from((last + 1) % self.seats.len())
+    }
+
+    // stud: the bring-in of seat i opens the first round
+    // Unlike a big blind, the bring-in has no option when everyone calls.
+    // min_raise: complete to bring_in + min_raise (no-limit, pot-limit)
+    pub(crate) fn post_bring_in(&mut self, i: usize, bring_in: u64, min_raise: u64) -> Progress {
+        self.post(i, Post::BringIn(bring_in));
+        self.current_bet = bring_in;
+        self.min_raise = min_raise;
+        self.bets = 0;
+        self.acted[i] = Some(bring_in);
+
+        self.start_from((i + 1) % self.seats.len())
+    }
+
+    // a seat returning after missing blinds, before the first action
+    // The big blind is live (the seat gets the option), the small blind dead.
+    pub(crate) fn post_missed_blinds(
+        &mut self, config: &GameConfig, i: usize, small: bool, big: bool) -> Result<(), GameError>
+    {
+        let posted = self.posts.iter().any(|&(j, post)| j == i && !matches!(post, Post::Ante(_)));
+        if self.started || self.finished || !self.seats[i].can_act() || posted {
+            return Err(GameError::InvalidPost(i))
+        }
+
+        if big {
+            self.post(i, Post::BigBlind(config.big_blind));
+        }
+        if small {
+            self.post(i, Post::DeadSmallBlind(config.small_blind));
+        }
+
+        Ok(())
+    }
+
+    // clear the bets for the next round
+    // => there is betting (at least two seats have chips)
+    pub(crate) fn new_round(&mut self, min_raise: u64, big_bet: bool) -> bool {
+        for seat in self.seats.iter_mut() {
+            seat.bet = 0;
+        }
+        for acted in self.acted.iter_mut() {
+            *acted = None;
+        }
+        self.to_act = None;
+        self.current_bet = 0;
+        self.min_raise = min_raise;
+        self.bets = 0;
+        self.big_bet = big_bet;
+
+        self.seats.iter().filter(|s| s.can_act()).count() >= 2
+    }
+
+    // the first seat from first (inclusive) which needs to act
+    pub(crate) fn start_from(&mut self, first: usize) -> Progress {
+        let n = self.seats.len();
+        self.to_act = (0..n).map(|k| (first + k) % n).find(|&j| self.needs_action(j));
+        if self.to_act.is_some() { Progress::Next } else { Progress::RoundOver }
+    }
+
+    fn needs_action(&self, i: usize) -> bool {
+        self.seats[i].can_act() && (self.acted[i].is_none() || self.seats[i].bet < self.current_bet)
+    }
+
+    fn bet_state(&self, i: usize) -> BetState {
+        BetState {
+            current_bet: self.current_bet,
+            min_raise: self.min_raise,
+            bets: self.bets,
+            to_call: self.current_bet.saturating_sub(self.seats[i].bet),
+            pot: self.pot(),
+            big_bet: self.big_bet,
+        }
+    }
+
+    pub fn legal_actions(&self) -> LegalActions {
+        let i = match self.to_act {
+            Some(i) => i,
+            None => return LegalActions::default(),
+        };
+        let seat = &self.seats[i];
+        let to_call = self.current_bet.saturating_sub(seat.bet);
+        let max_to = seat.bet + seat.stack;
+        let state = self.bet_state(i);
+        // raising is pointless if nobody else can call it
+        let others = self.seats.iter().enumerate().any(|(j, s)| j != i && s.can_act());
+        // after an incomplete raise, only call or fold
+        let reopened = self.acted[i]
+            .is_none_or(|from| self.structure.reopens(&state, from, self.current_bet));
+        let range = if others && reopened && seat.stack > to_call {
+            self.structure.raise_range(&state)
+        }
+        else {
+            None
+        };
+
+        let mut legal = LegalActions {
+            fold: to_call > 0,
+            check: to_call == 0,
+            call: if to_call > 0 { Some(to_call.min(seat.stack)) } else { None },
+            ..LegalActions::default()
+        };
+        if let Some((min, max)) = range {
+            // short of the minimum: all-in only
+            let range = Some((min.min(max_to), max.min(max_to)));
+            if self.current_bet == 0 {
+                legal.bet = range;
+            }
+            else {
+                legal.raise = range;
+            }
+        }
+        if range.is_some_and(|(_, max)| max_to <= max) || seat.stack <= to_call {
+            legal.all_in = Some(max_to);
+        }
+
+        legal
+    }
+
+    pub(crate) fn act(&mut self, action: Action) -> Result<Progress, GameError> {
+        let i = self.to_act.ok_or(GameError::Finished)?;
+        if !self.legal_actions().contains(action) {
+            return Err(GameError::IllegalAction(action))
+        }
+
+        let seat = &self.seats[i];
+        match action {
+            Action::Fold => self.seats[i].folded = true,
+            Action::Check => (),
+            Action::Call => {
+                self.put(i, self.current_bet - seat.bet);
+            }
+            Action::Bet(to) | Action::Raise(to) => self.raise_to(i, to),
+            Action::AllIn => {
+                let to = seat.bet + seat.stack;
+                if to > self.current_bet {
+                    self.raise_to(i, to);
+                }
+                else {
+                    self.put(i, seat.stack);
+                }
+            }
+        }
+        self.acted[i] = Some(self.current_bet);
+        self.started = true;
+
+        if self.seats.iter().filter(|s| s.in_hand()).count() == 1 {
+            let values: Vec<Option<()>> = self.seats.iter().map(|s| s.in_hand().then_some(())).collect();
+            self.finish(&values, &left_of_button(self.seats.len(), self.button));
+            return Ok(Progress::HandOver)
+        }
+
+        Ok(self.start_from((i + 1) % self.seats.len()))
+    }
+
+    // everyone else has to act again, but only a full raise lets the
+    // seats which have acted raise again
+    fn raise_to(&mut self, i: usize, to: u64) {
+        let state = self.bet_state(i);
+        if self.structure.reopens(&state, self.current_bet, to) {
+            self.min_raise = self.min_raise.max(to - self.current_bet);
+            self.bets += 1;
+        }
+        self.put(i, to - self.seats[i].bet);
+        self.current_bet = to;
+    }
+
+    // split the pots between the best values (None = can not win)
+    // odd_order: all seats in the order of receiving odd chips
+    pub(crate) fn finish<V: Ord>(&mut self, values: &[Option<V>], odd_order: &[usize]) {
+        let committed: Vec<u64> = self.seats.iter().map(|s| s.committed).collect();
+        let live: Vec<bool> = self.seats.iter().map(|s| s.in_hand()).collect();
+        let pots = build_pots(&committed, &live);
+        self.breakdown = award(&pots, values, odd_order);
+        for (seat, &payout) in self.seats.iter_mut().zip(&self.breakdown.payouts) {
+            seat.stack += payout;
+        }
+        self.to_act = None;
+        self.finished = true;
+    }
+}
@@ -0,0 +1,283 @@
+// Typed wrappers of card codes
+//
+// The evaluator works on plain u32 codes (see cards::encode()).
+// These types add the text notation ("As", "As Kh Qd") and,
+// with the "serde" feature, serialization as the same text.
+// hand_description() names a HandValue in words ("a pair of Kings").
+use core::fmt;
+use core::fmt::Write;
+use core::str::FromStr;
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec::Vec};
+use crate::cards::{self, Rank, CARDS_NUM, HAND_SIZE, NUMBER_CHARS, SUIT_CHARS};
+
+// invalid text at pos (byte offset)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub pos: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid text at {}", self.pos)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Card(u32);
+
+impl Card {
+    pub fn new(code: u32) -> Self {
+        assert!(code < CARDS_NUM);
+
+        Card(code)
+    }
+
+    pub fn from_parts(suit: u32, number: u32) -> Self {
+        Card(cards::encode(suit, number))
+    }
+
+    pub fn code(self) -> u32 {
+        self.0
+    }
+
+    pub fn suit(self) -> u32 {
+        cards::decode(self.0).0
+    }
+
+    pub fn number(self) -> u32 {
+        cards::decode(self.0).1
+    }
+}
+
+impl From<Card> for u32 {
+    fn from(card: Card) -> u32 {
+        card.0
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_char(NUMBER_CHARS[self.number() as usize])?;
+        f.write_char(SUIT_CHARS[self.suit() as usize])
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        cards::from_text(s.trim()).map(Card).ok_or(ParseError { pos: 0 })
+    }
+}
+
+// list of cards (hole cards, board, 5-cards hand, ...)
+// serialized as ["As", "Kh", "Qd"]
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Hand(pub Vec<Card>);
+
+#[cfg(feature = "alloc")]
+impl Hand {
+    pub fn from_codes(codes: &[u32]) -> Self {
+        Hand(codes.iter().map(|&code| Card::new(code)).collect())
+    }
+
+    pub fn codes(&self) -> Vec<u32> {
+        self.0.iter().map(|card| card.0).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&cards::list_to_text(&self.codes()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromStr for Hand {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        cards::list_from_text(s)
+            .map(|codes| Hand::from_codes(&codes))
+            .map_err(|pos| ParseError { pos })
+    }
+}
+
+// result of calc_hand(), compared by rank then order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandValue {
+    pub rank: Rank,
+    pub order: u32,
+}
+
+impl HandValue {
+    // the best 5 cards in 5..=7 cards
+    pub fn of(codes: &[u32]) -> Self {
+        if codes.len() == HAND_SIZE {
+            cards::calc_hand(codes).into()
+        }
+        else {
+            cards::calc_best(codes).into()
+        }
+    }
+}
+
+impl From<(Rank, u32)> for HandValue {
+    fn from((rank, order): (Rank, u32)) -> Self {
+        HandValue { rank, order }
+    }
+}
+
+#[cfg(feature = "alloc")]
+const NUMBER_NAMES: [&str; cards::NUMBER_NUM as usize] = [
+    "Deuce", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten", "Jack", "Queen", "King", "Ace",
+];
+
+#[cfg(feature = "alloc")]
+fn number_name(number: u32) -> &'static str {
+    NUMBER_NAMES[number as usize]
+}
+
+#[cfg(feature = "alloc")]
+fn plural(number: u32) -> String {
+    match number {
+        4 => "Sixes".into(),
+        _ => format!("{}s", number_name(number)),
+    }
+}
+
+// numbers in the order of HandValue::order (see cards::calc_hand())
+#[cfg(feature = "alloc")]
+fn order_numbers(value: &HandValue) -> Vec<u32> {
+    let count = match value.rank {
+        Rank::Straight | Rank::StraightFlash => 1,
+        Rank::Quads | Rank::FullHouse => 2,
+        Rank::Trips | Rank::TwoPair => 3,
+        Rank::OnePair => 4,
+        Rank::HighCard | Rank::Flash => 5,
+    };
+    (0..count).rev().map(|k| value.order >> (k * 4) & 0xf).collect()
+}
+
+// "a pair of Kings", "a straight, Ten to Ace", ...
+#[cfg(feature = "alloc")]
+pub fn hand_description(value: &HandValue) -> String {
+    let n = order_numbers(value);
+    // the wheel is Five high
+    let low = |top: u32| if top == 3 { 12 } else { top - 4 };
+    match value.rank {
+        Rank::HighCard => format!("high card {}", number_name(n[0])),
+        Rank::OnePair => format!("a pair of {}", plural(n[0])),
+        Rank::TwoPair => format!("two pair, {} and {}", plural(n[0]), plural(n[1])),
+        Rank::Trips => format!("three of a kind, {}", plural(n[0])),
+        Rank::Straight => format!("a straight, {} to {}", number_name(low(n[0])), number_name(n[0])),
+        Rank::Flash => format!("a flush, {} high", number_name(n[0])),
+        Rank::FullHouse => format!("a full house, {} full of {}", plural(n[0]), plural(n[1])),
+        Rank::Quads => format!("four of a kind, {}", plural(n[0])),
+        Rank::StraightFlash if n[0] == cards::NUMBER_NUM - 1 => "a Royal Flush".into(),
+        Rank::StraightFlash => format!("a straight flush, {} to {}", number_name(low(n[0])), number_name(n[0])),
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use alloc::{format, string::String};
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use serde::de::Error;
+    use super::*;
+
+    // "As"
+    impl Serialize for Card {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Card {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let text = String::deserialize(deserializer)?;
+            text.parse().map_err(|_| D::Error::custom(format!("invalid card: {}", text)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn card() {
+        let card: Card = "Td".parse().unwrap();
+        assert_eq!(card, Card::from_parts(1, 8));
+        assert_eq!(card.suit(), 1);
+        assert_eq!(card.number(), 8);
+        assert_eq!(u32::from(card), cards::encode(1, 8));
+        assert_eq!(card.to_string(), "Td");
+        assert!("Tx".parse::<Card>().is_err());
+    }
+
+    #[test]
+    fn hand() {
+        let hand: Hand = "AsKsQsJsTs".parse().unwrap();
+        assert_eq!(hand.len(), 5);
+        assert_eq!(hand.to_string(), "As Ks Qs Js Ts");
+        assert_eq!(HandValue::of(&hand.codes()).rank, Rank::StraightFlash);
+        assert_eq!("As Kz".parse::<Hand>(), Err(ParseError { pos: 3 }));
+    }
+
+    #[test]
+    fn hand_value() {
+        let flash = HandValue::of(&"2h 5h 9h Jh Kh".parse::<Hand>().unwrap().codes());
+        let straight = HandValue::of(&"Ah 2c 3d 4s 5h 5c Kd".parse::<Hand>().unwrap().codes());
+        assert_eq!(flash.rank, Rank::Flash);
+        assert_eq!(straight.rank, Rank::Straight);
+        assert!(flash > straight);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let hand: Hand = "As Kh".parse().unwrap();
+        let json = serde_json::to_string(&hand).unwrap();
+        assert_eq!(json, r#"["As","Kh"]"#);
+        assert_eq!(serde_json::from_str::<Hand>(&json).unwrap(), hand);
+        assert!(serde_json::from_str::<Card>(r#""Xs""#).is_err());
+
+        let value = HandValue { rank: Rank::Flash, order: 42 };
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"rank":"Flash","order":42}"#);
+        assert_eq!(serde_json::from_str::<HandValue>(&json).unwrap(), value);
+    }
+
+    #[test]
+    fn descriptions() {
+        let value = |text: &str| HandValue::of(&cards::list_from_text(text).unwrap());
+        assert_eq!(hand_description(&value("As Kd 9c 7h 2s")), "high card Ace");
+        assert_eq!(hand_description(&value("6s 6d 9c 7h 2s")), "a pair of Sixes");
+        assert_eq!(hand_description(&value("As Ad Kc Kh 2s")), "two pair, Aces and Kings");
+        assert_eq!(hand_description(&value("Ts Jd Qc Kh As")), "a straight, Ten to Ace");
+        assert_eq!(hand_description(&value("As 2d 3c 4h 5s")), "a straight, Ace to Five");
+        assert_eq!(hand_description(&value("Ks Kd Kc Th Ts")), "a full house, Kings full of Tens");
+        assert_eq!(hand_description(&value("2s 2d 2c 2h As")), "four of a kind, Deuces");
+        assert_eq!(hand_description(&value("9h Th Jh Qh Kh")), "a straight flush, Nine to King");
+        assert_eq!(hand_description(&value("Th Jh Qh Kh Ah")), "a Royal Flush");
+        assert_eq!(hand_description(&value("2h 7h 9h Jh Qh")), "a flush, Queen high");
+    }
+}
@@ -0,0 +1,254 @@
+// Python bindings of cardslib
+//
+// Build with maturin (see pyproject.toml), then:
+//   import cardspy
+//   cardspy.evaluate("As Ks Qs Js Ts 2c 2d")  # ("StraightFlash", 12, [...])
+//
+// Cards can be given as text ("As Kh") or a list of codes
+// (cardslib::cards::encode()). Invalid input raises ValueError.
+// Arrays are returned as numpy arrays.
+use numpy::{PyArray1, PyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use cardslib::cards::{self, Rank, RANKS, CARDS_NUM, HAND_SIZE};
+use cardslib::equity::{self as eq, HOLE_SIZE, BOARD_SIZE};
+use cardslib::odds;
+
+// text or codes
+#[derive(FromPyObject)]
+enum CardsArg {
+    Text(String),
+    Codes(Vec<u32>),
+}
+
+impl CardsArg {
+    // => codes without duplication
+    fn codes(&self) -> PyResult<Vec<u32>> {
+        let codes = match self {
+            CardsArg::Text(text) => cards::list_from_text(text).map_err(|pos| {
+                PyValueError::new_err(format!("invalid card at {}: {}", pos, text))
+            })?,
+            CardsArg::Codes(codes) => codes.clone(),
+        };
+        for (i, &code) in codes.iter().enumerate() {
+            if code >= CARDS_NUM {
+                return Err(PyValueError::new_err(format!("invalid card code: {}", code)))
+            }
+            if codes[..i].contains(&code) {
+                return Err(PyValueError::new_err(
+                    format!("duplicate card: {}", cards::to_text(code))))
+            }
+        }
+
+        Ok(codes)
+    }
+
+    fn hole(&self) -> PyResult<[u32; HOLE_SIZE]> {
+        let codes = self.codes()?;
+        if codes.len() != HOLE_SIZE {
+            return Err(PyValueError::new_err("hole cards must be 2 cards"))
+        }
+
+        Ok([codes[0], codes[1]])
+    }
+}
+
+fn rank_name(rank: Rank) -> String {
+    format!("{:?}", rank)
+}
+
+fn check_distinct(codes: &[u32]) -> PyResult<()> {
+    for (i, code) in codes.iter().enumerate() {
+        if codes[..i].contains(code) {
+            return Err(PyValueError::new_err(
+                format!("duplicate card: {}", cards::to_text(*code))))
+        }
+    }
+
+    Ok(())
+}
+
+// "As Kh" => [51, 37]
+#[pyfunction]
+fn parse_cards(text: String) -> PyResult<Vec<u32>> {
+    CardsArg::Text(text).codes()
+}
+
+// [51, 37] => "As Kh"
+#[pyfunction]
+fn format_cards(codes: Vec<u32>) -> PyResult<String> {
+    CardsArg::Codes(codes).codes().map(|codes| cards::list_to_text(&codes))
+}
+
+// 5 cards => (rank name, order)
+#[pyfunction]
+fn calc_hand(hand: CardsArg) -> PyResult<(String, u32)> {
+    let codes = hand.codes()?;
+    if codes.len() != HAND_SIZE {
+        return Err(PyValueError::new_err("5 cards are required"))
+    }
+    let (rank, order) = cards::calc_hand(&codes);
+
+    Ok((rank_name(rank), order))
+}
+
+// 5..=7 cards => (rank name, order, best 5 codes)
+#[pyfunction]
+fn evaluate(hand: CardsArg) -> PyResult<(String, u32, Vec<u32>)> {
+    let codes = hand.codes()?;
+    if codes.len() < HAND_SIZE || codes.len() > HAND_SIZE + 2 {
+        return Err(PyValueError::new_err("5 to 7 cards are required"))
+    }
+    let (rank, order) = cards::calc_best(&codes);
+
+    Ok((rank_name(rank), order, cards::calc_best_cards(&codes).to_vec()))
+}
+
+// Lazy version of cards::all_case(): iterates cases without building the list
+// next() gives a list of codes, batch(n) a numpy array of shape (<= n, size).
+#[pyclass]
+struct AllCase {
+    // cards which can be used
+    avail: Vec<u32>,
+    // cards in a case
+    size: usize,
+    // current indices into avail (None = finished)
+    idx: Option<Vec<usize>>,
+}
+
+impl AllCase {
+    fn next_case(&mut self) -> Option<Vec<u32>> {
+        let avail = &self.avail;
+        let idx = self.idx.as_mut()?;
+        let case = idx.iter().map(|&i| avail[i]).collect();
+
+        // advance to the next combination
+        let n = avail.len();
+        let k = idx.len();
+        match (0..k).rev().find(|&i| idx[i] < n - k + i) {
+            Some(i) => {
+                idx[i] += 1;
+                for j in i + 1..k {
+                    idx[j] = idx[j - 1] + 1;
+                }
+            }
+            None => self.idx = None,
+        }
+
+        Some(case)
+    }
+}
+
+#[pymethods]
+impl AllCase {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<Vec<u32>> {
+        slf.next_case()
+    }
+
+    fn batch<'py>(&mut self, py: Python<'py>, n: usize) -> PyResult<Bound<'py, PyArray2<u32>>> {
+        let mut rows = Vec::new();
+        while rows.len() < n {
+            match self.next_case() {
+                Some(case) => rows.push(case),
+                None => break,
+            }
+        }
+        if rows.is_empty() {
+            return Ok(PyArray2::zeros(py, [0, self.size], false))
+        }
+
+        PyArray2::from_vec2(py, &rows).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+// all sets of size cards which do not contain fixed cards
+#[pyfunction]
+#[pyo3(signature = (fixed, size))]
+fn all_case(fixed: CardsArg, size: usize) -> PyResult<AllCase> {
+    let fixed = fixed.codes()?;
+    let avail: Vec<u32> = (0..CARDS_NUM).filter(|c| !fixed.contains(c)).collect();
+    if size > avail.len() {
+        return Err(PyValueError::new_err("size is too large"))
+    }
+
+    Ok(AllCase { avail, size, idx: Some((0..size).collect()) })
+}
+
+// hole cards of each player + board => numpy array of equities
+#[pyfunction]
+#[pyo3(signature = (hands, board=None))]
+fn equity<'py>(
+    py: Python<'py>, hands: Vec<CardsArg>, board: Option<CardsArg>)
+    -> PyResult<Bound<'py, PyArray1<f64>>>
+{
+    if hands.len() < 2 || hands.len() > 10 {
+        return Err(PyValueError::new_err("2 to 10 hands are required"))
+    }
+    let holes = hands.iter().map(|h| h.hole()).collect::<PyResult<Vec<_>>>()?;
+    let board = board.map_or(Ok(vec![]), |b| b.codes())?;
+    if board.len() > BOARD_SIZE {
+        return Err(PyValueError::new_err("board must be at most 5 cards"))
+    }
+    let mut all: Vec<u32> = holes.iter().flatten().copied().collect();
+    all.extend_from_slice(&board);
+    check_distinct(&all)?;
+
+    let result = py.detach(|| eq::equity(&holes, &board));
+
+    Ok(PyArray1::from_vec(py, result))
+}
+
+// Monte Carlo equity against random hands
+#[pyfunction]
+#[pyo3(signature = (hole, board=None, opponents=1, trials=10000, seed=0))]
+fn equity_vs_random(
+    py: Python<'_>, hole: CardsArg, board: Option<CardsArg>,
+    opponents: usize, trials: u32, seed: u64) -> PyResult<f64>
+{
+    let hole = hole.hole()?;
+    let board = board.map_or(Ok(vec![]), |b| b.codes())?;
+    if board.len() > BOARD_SIZE {
+        return Err(PyValueError::new_err("board must be at most 5 cards"))
+    }
+    check_distinct(&[&hole[..], &board[..]].concat())?;
+    if !(1..=9).contains(&opponents) || trials == 0 {
+        return Err(PyValueError::new_err("opponents must be 1..=9 and trials > 0"))
+    }
+
+    Ok(py.detach(|| eq::equity_vs_random(&hole, &board, opponents, trials, seed)))
+}
+
+// known cards + cards to come => numpy array of probabilities (index = RANK_NAMES)
+#[pyfunction]
+fn rank_distribution<'py>(
+    py: Python<'py>, known: CardsArg, to_come: usize) -> PyResult<Bound<'py, PyArray1<f64>>>
+{
+    let known = known.codes()?;
+    let size = known.len() + to_come;
+    if !(HAND_SIZE..=HAND_SIZE + 2).contains(&size) {
+        return Err(PyValueError::new_err("known + to_come must be 5 to 7"))
+    }
+
+    Ok(PyArray1::from_slice(py, &odds::rank_distribution(&known, to_come)))
+}
+
+#[pymodule]
+fn cardspy(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let names: Vec<String> = RANKS.iter().map(|&r| rank_name(r)).collect();
+    m.add("RANK_NAMES", names)?;
+    m.add_function(wrap_pyfunction!(parse_cards, m)?)?;
+    m.add_function(wrap_pyfunction!(format_cards, m)?)?;
+    m.add_function(wrap_pyfunction!(calc_hand, m)?)?;
+    m.add_function(wrap_pyfunction!(evaluate, m)?)?;
+    m.add_function(wrap_pyfunction!(all_case, m)?)?;
+    m.add_function(wrap_pyfunction!(equity, m)?)?;
+    m.add_function(wrap_pyfunction!(equity_vs_random, m)?)?;
+    m.add_function(wrap_pyfunction!(rank_distribution, m)?)?;
+    m.add_class::<AllCase>()?;
+
+    Ok(())
+}
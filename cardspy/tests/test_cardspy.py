@@ -0,0 +1,75 @@
+# Run after `maturin develop`:
+#   pytest
+import pytest
+
+import cardspy
+
+
+def test_parse_format():
+    assert cardspy.parse_cards("2c As") == [0, 51]
+    assert cardspy.format_cards([0, 51]) == "2c As"
+    with pytest.raises(ValueError):
+        cardspy.parse_cards("As Xs")
+    with pytest.raises(ValueError):
+        cardspy.parse_cards("As As")
+    with pytest.raises(ValueError):
+        cardspy.format_cards([52])
+
+
+def test_calc_hand():
+    assert cardspy.calc_hand("As Ks Qs Js Ts") == ("StraightFlash", 12)
+    assert cardspy.calc_hand([0, 13, 26, 39, 1])[0] == "Quads"
+    with pytest.raises(ValueError):
+        cardspy.calc_hand("As Ks")
+
+
+def test_evaluate():
+    rank, order, best = cardspy.evaluate("Ts Js 2c Qs 2d As Ks")
+    assert rank == "StraightFlash"
+    assert cardspy.format_cards(best) == "As Ks Qs Js Ts"
+    assert cardspy.RANK_NAMES[0] == "HighCard"
+    assert len(cardspy.RANK_NAMES) == 9
+
+
+def test_all_case():
+    cases = list(cardspy.all_case("As Ah", 2))
+    assert len(cases) == 50 * 49 // 2
+    assert cases[0] == [0, 1]
+    assert all(51 not in c and 38 not in c for c in cases)
+
+
+def test_all_case_batch():
+    np = pytest.importorskip("numpy")
+    it = cardspy.all_case([], 2)
+    first = it.batch(1000)
+    assert first.shape == (1000, 2)
+    assert first.dtype == np.uint32
+    rest = it.batch(10000)
+    assert rest.shape == (1326 - 1000, 2)
+    assert it.batch(10).shape == (0, 2)
+
+
+def test_equity():
+    np = pytest.importorskip("numpy")
+    result = cardspy.equity(["As Ah", "Ks Kh"], "Ad Kd 2c 7c")
+    assert isinstance(result, np.ndarray)
+    assert result[1] == pytest.approx(1 / 44)
+    assert result.sum() == pytest.approx(1.0)
+    with pytest.raises(ValueError):
+        cardspy.equity(["As Ah", "As Kh"])
+
+
+def test_equity_vs_random():
+    value = cardspy.equity_vs_random("As Ah", opponents=1, trials=5000, seed=1)
+    assert 0.8 < value < 0.9
+    assert value == cardspy.equity_vs_random("As Ah", opponents=1, trials=5000, seed=1)
+    with pytest.raises(ValueError):
+        cardspy.equity_vs_random("As Ah", opponents=0)
+
+
+def test_rank_distribution():
+    pytest.importorskip("numpy")
+    dist = cardspy.rank_distribution("Ah 5h Th 2h 7s", 2)
+    assert dist.sum() == pytest.approx(1.0)
+    flash = dist[cardspy.RANK_NAMES.index("Flash")] + dist[cardspy.RANK_NAMES.index("StraightFlash")]
+    assert flash == pytest.approx(1 - (38 * 37) / (47 * 46))
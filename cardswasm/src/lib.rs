@@ -0,0 +1,230 @@
+// WebAssembly bindings of cardslib for the web client
+//
+// Build for browsers:
+//   wasm-pack build --target web
+// The same rules as the server (playsv) are used since both call cardslib.
+//
+// Cards are text ("As Kh Qd") or codes of cardslib::cards::encode().
+// Invalid input throws a JS Error instead of panicking.
+// The logic is in plain Rust functions (*_impl) so that it can be tested
+// natively (cargo test) and as wasm under wasmtime (./test-wasm.sh).
+use wasm_bindgen::prelude::*;
+use cardslib::cards::{self, Rank, HAND_SIZE};
+use cardslib::equity::{self as eq, HOLE_SIZE, BOARD_SIZE};
+
+// text => codes, without duplication
+fn parse_impl(text: &str) -> Result<Vec<u32>, String> {
+    let codes = cards::list_from_text(text)
+        .map_err(|pos| format!("invalid card at {}: {}", pos, text))?;
+    for (i, code) in codes.iter().enumerate() {
+        if codes[..i].contains(code) {
+            return Err(format!("duplicate card: {}", cards::to_text(*code)))
+        }
+    }
+
+    Ok(codes)
+}
+
+fn parse_hole(text: &str) -> Result<[u32; HOLE_SIZE], String> {
+    let codes = parse_impl(text)?;
+    if codes.len() != HOLE_SIZE {
+        return Err(format!("hole cards must be {} cards: {}", HOLE_SIZE, text))
+    }
+
+    Ok([codes[0], codes[1]])
+}
+
+fn rank_name(rank: Rank) -> &'static str {
+    match rank {
+        Rank::HighCard => "HighCard",
+        Rank::OnePair => "OnePair",
+        Rank::TwoPair => "TwoPair",
+        Rank::Trips => "Trips",
+        Rank::Straight => "Straight",
+        Rank::Flash => "Flash",
+        Rank::FullHouse => "FullHouse",
+        Rank::Quads => "Quads",
+        Rank::StraightFlash => "StraightFlash",
+    }
+}
+
+// result of evaluate()
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    rank: Rank,
+    order: u32,
+    best: [u32; HAND_SIZE],
+}
+
+#[wasm_bindgen]
+impl Evaluation {
+    // "Flash", "FullHouse", ...
+    #[wasm_bindgen(getter)]
+    pub fn rank(&self) -> String {
+        rank_name(self.rank).to_string()
+    }
+
+    // 0 (HighCard) ..= 8 (StraightFlash)
+    #[wasm_bindgen(getter, js_name = rankIndex)]
+    pub fn rank_index(&self) -> u32 {
+        self.rank as u32
+    }
+
+    // compare in the same rank
+    #[wasm_bindgen(getter)]
+    pub fn order(&self) -> u32 {
+        self.order
+    }
+
+    // the best 5 cards ("As Ks Qs Js Ts")
+    #[wasm_bindgen(getter)]
+    pub fn best(&self) -> String {
+        cards::list_to_text(&self.best)
+    }
+}
+
+fn evaluate_impl(text: &str) -> Result<Evaluation, String> {
+    let codes = parse_impl(text)?;
+    if codes.len() < HAND_SIZE || codes.len() > HAND_SIZE + 2 {
+        return Err(format!("5 to 7 cards are required: {}", text))
+    }
+    let (rank, order) = cards::calc_best(&codes);
+
+    Ok(Evaluation { rank, order, best: cards::calc_best_cards(&codes) })
+}
+
+fn compare_impl(a: &str, b: &str) -> Result<i32, String> {
+    let ea = evaluate_impl(a)?;
+    let eb = evaluate_impl(b)?;
+
+    Ok((ea.rank, ea.order).cmp(&(eb.rank, eb.order)) as i32)
+}
+
+fn equity_impl(hands: &[String], board: &str) -> Result<Vec<f64>, String> {
+    if hands.len() < 2 || hands.len() > 10 {
+        return Err("2 to 10 hands are required".to_string())
+    }
+    let holes = hands.iter()
+        .map(|h| parse_hole(h))
+        .collect::<Result<Vec<_>, _>>()?;
+    let board = parse_impl(board)?;
+    if board.len() > BOARD_SIZE {
+        return Err(format!("board must be at most {} cards", BOARD_SIZE))
+    }
+    let mut all: Vec<u32> = holes.iter().flatten().copied().collect();
+    all.extend_from_slice(&board);
+    for (i, code) in all.iter().enumerate() {
+        if all[..i].contains(code) {
+            return Err(format!("duplicate card: {}", cards::to_text(*code)))
+        }
+    }
+
+    Ok(eq::equity(&holes, &board))
+}
+
+fn equity_vs_random_impl(
+    hole: &str, board: &str,
+    opponents: usize, trials: u32, seed: u64) -> Result<f64, String>
+{
+    let hole = parse_hole(hole)?;
+    let board = parse_impl(board)?;
+    if board.len() > BOARD_SIZE || board.iter().any(|c| hole.contains(c)) {
+        return Err("invalid board".to_string())
+    }
+    if !(1..=9).contains(&opponents) || trials == 0 {
+        return Err("opponents must be 1..=9 and trials > 0".to_string())
+    }
+
+    Ok(eq::equity_vs_random(&hole, &board, opponents, trials, seed))
+}
+
+fn js_err(msg: String) -> JsError {
+    JsError::new(&msg)
+}
+
+// "As Kh" => [51, 37]
+#[wasm_bindgen(js_name = parseCards)]
+pub fn parse_cards(text: &str) -> Result<Vec<u32>, JsError> {
+    parse_impl(text).map_err(js_err)
+}
+
+// [51, 37] => "As Kh"
+#[wasm_bindgen(js_name = formatCards)]
+pub fn format_cards(codes: Vec<u32>) -> Result<String, JsError> {
+    if let Some(code) = codes.iter().find(|&&c| c >= cards::CARDS_NUM) {
+        return Err(js_err(format!("invalid code: {}", code)))
+    }
+
+    Ok(cards::list_to_text(&codes))
+}
+
+// 5..=7 cards => rank of the best 5 cards
+#[wasm_bindgen]
+pub fn evaluate(text: &str) -> Result<Evaluation, JsError> {
+    evaluate_impl(text).map_err(js_err)
+}
+
+// -1 (a < b), 0 (tie), 1 (a > b)
+#[wasm_bindgen]
+pub fn compare(a: &str, b: &str) -> Result<i32, JsError> {
+    compare_impl(a, b).map_err(js_err)
+}
+
+// hole cards of each player + known board => equity of each player
+#[wasm_bindgen]
+pub fn equity(hands: Vec<String>, board: &str) -> Result<Vec<f64>, JsError> {
+    equity_impl(&hands, board).map_err(js_err)
+}
+
+// Monte Carlo equity against random hands (seed for reproducibility)
+#[wasm_bindgen(js_name = equityVsRandom)]
+pub fn equity_vs_random(
+    hole: &str, board: &str,
+    opponents: usize, trials: u32, seed: u64) -> Result<f64, JsError>
+{
+    equity_vs_random_impl(hole, board, opponents, trials, seed).map_err(js_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_impl("2c As"), Ok(vec![0, 51]));
+        assert!(parse_impl("As Xs").is_err());
+        assert!(parse_impl("As As").is_err());
+    }
+
+    #[test]
+    fn evaluate() {
+        let result = evaluate_impl("Ts Js 2c Qs 2d As Ks").unwrap();
+        assert_eq!(result.rank(), "StraightFlash");
+        assert_eq!(result.rank_index(), 8);
+        assert_eq!(result.best(), "As Ks Qs Js Ts");
+        assert!(evaluate_impl("As Ks").is_err());
+        assert!(evaluate_impl("As Ks Qs Js Ts 9s 8s 7s").is_err());
+    }
+
+    #[test]
+    fn compare() {
+        assert_eq!(compare_impl("As Ad Kc Kd 2s", "Qs Qd Qc 3d 2c"), Ok(-1));
+        assert_eq!(compare_impl("As Ad Kc Kd 2s", "Ac Ah Ks Kh 2d"), Ok(0));
+        assert_eq!(compare_impl("2h 5h 9h Jh Kh", "Ah 2c 3d 4s 5s"), Ok(1));
+        assert!(compare_impl("As", "Ac Ah Ks Kh 2d").is_err());
+    }
+
+    #[test]
+    fn equity() {
+        let hands = vec!["As Ah".to_string(), "Ks Kh".to_string()];
+        let result = equity_impl(&hands, "Ad Kd 2c 7c").unwrap();
+        assert!((result[1] - 1.0 / 44.0).abs() < 1e-9);
+        assert!(equity_impl(&hands, "As Kd 2c").is_err());
+        assert!(equity_impl(&hands[..1], "").is_err());
+
+        let value = equity_vs_random_impl("As Ah", "", 1, 2000, 1).unwrap();
+        assert!(value > 0.8 && value < 0.9);
+        assert!(equity_vs_random_impl("As Ah", "", 0, 2000, 1).is_err());
+    }
+}
@@ -0,0 +1,10 @@
+#!/bin/sh
+# Build the tests for wasm32-wasip1 and run them under wasmtime
+# (the runner in .cargo/config.toml), in addition to `cargo test`.
+# Requires: rustup and wasmtime (https://wasmtime.dev) in PATH.
+set -e
+cd "$(dirname "$0")"
+
+command -v wasmtime > /dev/null || { echo "wasmtime not found" >&2; exit 1; }
+rustup target add wasm32-wasip1
+cargo test --target wasm32-wasip1 "$@"
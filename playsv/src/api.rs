@@ -0,0 +1,252 @@
+// Hand evaluation API (JSON)
+//
+// POST /api/v1/evaluate {"cards": "As Ks Qs Js Ts 2c 3d"}
+//   => {"rank": "StraightFlash", "order": 12, "best": ["As", "Ks", "Qs", "Js", "Ts"],
+//       "description": "a Royal Flush"}
+// POST /api/v1/compare {"hands": ["As Ah", "Kd Kc"], "board": "2c 7d 9h Js 4c"}
+//   => {"results": [{..evaluation.., "place": 1}, ..], "winners": [0], "tie": false}
+//
+// Each hand (with the board) has 5 to 7 cards, and a card can only be
+// used once. Bad requests get status 400 and {"error": "..."}.
+use std::fmt;
+use actix_web::{error, post, web, HttpRequest, HttpResponse, ResponseError};
+use actix_web::http::StatusCode;
+use serde::{Serialize, Deserialize};
+use cardslib::cards::{self, Rank, CARDS_NUM, HAND_SIZE};
+use cardslib::types::{hand_description, Hand, HandValue};
+
+const MAX_CARDS: usize = 7;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiError {
+    pub error: String,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.error)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::BadRequest().json(self)
+    }
+}
+
+fn bad_request(error: String) -> ApiError {
+    ApiError { error }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EvaluateRequest {
+    pub cards: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareRequest {
+    pub hands: Vec<String>,
+    // shared by all hands
+    #[serde(default)]
+    pub board: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Evaluation {
+    pub rank: Rank,
+    pub order: u32,
+    // highest first
+    pub best: Hand,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Ranked {
+    #[serde(flatten)]
+    pub evaluation: Evaluation,
+    // 1 = best, equal hands share the place
+    pub place: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Comparison {
+    pub results: Vec<Ranked>,
+    pub winners: Vec<usize>,
+    pub tie: bool,
+}
+
+// "As Ks" => codes, used: cards already taken
+fn parse_cards(text: &str, what: &str, used: &mut u64) -> Result<Vec<u32>, ApiError> {
+    let codes = cards::list_from_text(text)
+        .map_err(|pos| bad_request(format!("{}: invalid card at {}", what, pos)))?;
+    for &code in codes.iter() {
+        if *used >> code & 1 == 1 {
+            return Err(bad_request(format!("duplicate card: {}", cards::to_text(code))))
+        }
+        *used |= 1 << code;
+    }
+
+    Ok(codes)
+}
+
+fn evaluate_codes(codes: &[u32], what: &str) -> Result<Evaluation, ApiError> {
+    if !(HAND_SIZE..=MAX_CARDS).contains(&codes.len()) {
+        return Err(bad_request(format!("{}: {} cards, {} to {} needed", what, codes.len(), HAND_SIZE, MAX_CARDS)))
+    }
+    let value = HandValue::of(codes);
+    let mut best = cards::calc_best_cards(codes);
+    best.sort_unstable_by_key(|&c| std::cmp::Reverse(cards::card_order(c)));
+
+    Ok(Evaluation {
+        rank: value.rank,
+        order: value.order,
+        best: Hand::from_codes(&best),
+        description: hand_description(&value),
+    })
+}
+
+pub fn evaluate_cards(request: &EvaluateRequest) -> Result<Evaluation, ApiError> {
+    let codes = parse_cards(&request.cards, "cards", &mut 0)?;
+    evaluate_codes(&codes, "cards")
+}
+
+pub fn compare_hands(request: &CompareRequest) -> Result<Comparison, ApiError> {
+    // at most 52 cards in all
+    if request.hands.len() < 2 || request.hands.len() > CARDS_NUM as usize / 2 {
+        return Err(bad_request(format!("{} hands, 2 to {} needed", request.hands.len(), CARDS_NUM / 2)))
+    }
+    let mut used = 0u64;
+    let board = parse_cards(&request.board, "board", &mut used)?;
+    let mut evaluations = Vec::with_capacity(request.hands.len());
+    let mut values = Vec::with_capacity(request.hands.len());
+    for (i, text) in request.hands.iter().enumerate() {
+        let what = format!("hand {}", i);
+        let mut codes = parse_cards(text, &what, &mut used)?;
+        codes.extend_from_slice(&board);
+        let evaluation = evaluate_codes(&codes, &what)?;
+        values.push(HandValue { rank: evaluation.rank, order: evaluation.order });
+        evaluations.push(evaluation);
+    }
+
+    let results: Vec<Ranked> = evaluations.into_iter().zip(values.iter())
+        .map(|(evaluation, value)| Ranked { evaluation, place: 1 + values.iter().filter(|v| *v > value).count() })
+        .collect();
+    let winners: Vec<usize> = (0..results.len()).filter(|&i| results[i].place == 1).collect();
+
+    Ok(Comparison { tie: winners.len() > 1, results, winners })
+}
+
+#[post("/evaluate")]
+async fn evaluate(request: web::Json<EvaluateRequest>) -> Result<HttpResponse, ApiError> {
+    Ok(HttpResponse::Ok().json(evaluate_cards(&request)?))
+}
+
+#[post("/compare")]
+async fn compare(request: web::Json<CompareRequest>) -> Result<HttpResponse, ApiError> {
+    Ok(HttpResponse::Ok().json(compare_hands(&request)?))
+}
+
+// malformed JSON, missing fields, ...
+fn json_error(err: error::JsonPayloadError, _: &HttpRequest) -> actix_web::Error {
+    let response = ApiError { error: err.to_string() }.error_response();
+    error::InternalError::from_response(err, response).into()
+}
+
+pub fn routes(config: &mut web::ServiceConfig) {
+    config.service(
+        web::scope("/api/v1")
+            .app_data(web::JsonConfig::default().error_handler(json_error))
+            .service(evaluate)
+            .service(compare)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+    use serde_json::{json, Value};
+
+    fn evaluate_text(cards: &str) -> Result<Evaluation, ApiError> {
+        evaluate_cards(&EvaluateRequest { cards: cards.to_string() })
+    }
+
+    fn compare_texts(hands: &[&str], board: &str) -> Result<Comparison, ApiError> {
+        compare_hands(&CompareRequest { hands: hands.iter().map(|h| h.to_string()).collect(), board: board.to_string() })
+    }
+
+    #[test]
+    fn evaluation() {
+        let result = evaluate_text("2c Ts As Js 3d Ks Qs").unwrap();
+        assert_eq!(result.rank, Rank::StraightFlash);
+        assert_eq!(result.best.to_string(), "As Ks Qs Js Ts");
+        assert_eq!(result.description, "a Royal Flush");
+
+        let result = evaluate_text("Kd 9c Kh 9s 2d").unwrap();
+        assert_eq!(result.rank, Rank::TwoPair);
+        assert_eq!(result.best.to_string(), "Kh Kd 9s 9c 2d");
+        assert_eq!(result.description, "two pair, Kings and Nines");
+
+        let error = |cards: &str| evaluate_text(cards).unwrap_err().error;
+        assert_eq!(error("As Kx Qs Js Ts"), "cards: invalid card at 3");
+        assert_eq!(error("As Ks Qs Js As"), "duplicate card: As");
+        assert_eq!(error("As Ks Qs Js"), "cards: 4 cards, 5 to 7 needed");
+    }
+
+    #[test]
+    fn comparison() {
+        let result = compare_texts(&["As Ah", "Kd Kc", "Ac Ad"], "2c 7d 9h Js 4c").unwrap();
+        assert_eq!(result.winners, vec![0, 2]);
+        assert!(result.tie);
+        let places: Vec<usize> = result.results.iter().map(|r| r.place).collect();
+        assert_eq!(places, vec![1, 3, 1]);
+        assert_eq!(result.results[1].evaluation.description, "a pair of Kings");
+
+        // full hands without a board
+        let result = compare_texts(&["2c 3c 4c 5c 7c", "Ah Kh Qh Jh 9d 2d"], "").unwrap();
+        assert_eq!((result.winners.clone(), result.tie), (vec![0], false));
+
+        let error = |hands: &[&str], board: &str| compare_texts(hands, board).unwrap_err().error;
+        assert_eq!(error(&["As Ah"], "2c 7d 9h"), "1 hands, 2 to 26 needed");
+        assert_eq!(error(&["As Ah", "As Kd"], "2c 7d 9h"), "duplicate card: As");
+        assert_eq!(error(&["As Ah", "Kd 7d"], "2c 7d 9h"), "duplicate card: 7d");
+        assert_eq!(error(&["As Ah", "Kd"], "2c 7d 9h"), "hand 1: 4 cards, 5 to 7 needed");
+        assert_eq!(error(&["As Ah", "Kd Kc"], "2c 7d 9h 1s"), "board: invalid card at 9");
+    }
+
+    async fn post(uri: &str, body: Value) -> (StatusCode, Value) {
+        let mut app = test::init_service(App::new().configure(routes)).await;
+        let request = test::TestRequest::post().uri(uri).set_json(&body).to_request();
+        let response = test::call_service(&mut app, request).await;
+        let status = response.status();
+        (status, test::read_body_json(response).await)
+    }
+
+    #[actix_rt::test]
+    async fn endpoints() {
+        let (status, body) = post("/api/v1/evaluate", json!({"cards": "Ah Kh Qh Jh 9h"})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({
+            "rank": "Flash", "order": body["order"], "best": ["Ah", "Kh", "Qh", "Jh", "9h"],
+            "description": "a flush, Ace high",
+        }));
+
+        let (status, body) = post("/api/v1/compare", json!({"hands": ["As Ah", "Kd Kc"], "board": "2c 7d 9h Js 4c"})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["winners"], json!([0]));
+        assert_eq!(body["tie"], json!(false));
+        assert_eq!(body["results"][1]["place"], json!(2));
+        assert_eq!(body["results"][0]["rank"], json!("OnePair"));
+
+        let (status, body) = post("/api/v1/evaluate", json!({"cards": "As As Ks Qs Js"})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, json!({"error": "duplicate card: As"}));
+        let (status, body) = post("/api/v1/compare", json!({"board": "2c"})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("missing field `hands`"));
+    }
+}
@@ -0,0 +1,233 @@
+// Server configuration
+//
+// Each setting is taken from, in order of precedence:
+//   1. command line flags    --host 0.0.0.0 --port 80 --workers 4 --log-level debug
+//   2. environment variables PLAYSV_HOST, PLAYSV_PORT, PLAYSV_WORKERS, PLAYSV_LOG_LEVEL
+//   3. a TOML file           --config FILE or PLAYSV_CONFIG (optional)
+//   4. defaults              127.0.0.1:8080, a worker per CPU, info
+// The TOML file has the same keys: host, port, workers, log_level.
+use std::fmt;
+use std::fs;
+use std::net::IpAddr;
+use serde::{Serialize, Deserialize};
+
+pub const USAGE: &str = "\
+[--config FILE] [--host HOST] [--port PORT] [--workers N] [--log-level LEVEL] [--print-config]
+  LEVEL: off, error, warn, info, debug or trace";
+
+const LOG_LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // an IP address or a host name
+    pub host: String,
+    pub port: u16,
+    pub workers: usize,
+    pub log_level: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+            workers: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            log_level: "info".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    Usage,
+    // flag or variable, value
+    Invalid(String, String),
+    File(String, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Usage => write!(f, "usage: playsv {}", USAGE),
+            ConfigError::Invalid(name, value) => write!(f, "invalid {}: {:?}", name, value),
+            ConfigError::File(path, error) => write!(f, "{}: {}", path, error),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// what main() does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub config: Config,
+    pub print_config: bool,
+}
+
+fn invalid(name: &str, value: &str) -> ConfigError {
+    ConfigError::Invalid(name.to_string(), value.to_string())
+}
+
+impl Config {
+    // the value of a setting from a flag or a variable called name
+    fn set(&mut self, key: &str, name: &str, value: &str) -> Result<(), ConfigError> {
+        match key {
+            "host" => self.host = value.to_string(),
+            "port" => self.port = value.parse().map_err(|_| invalid(name, value))?,
+            "workers" => self.workers = value.parse().map_err(|_| invalid(name, value))?,
+            "log_level" => self.log_level = value.to_string(),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let host_name = |h: &str| !h.is_empty() && h.len() <= 253
+            && h.split('.').all(|label| !label.is_empty() && label.len() <= 63
+                && !label.starts_with('-') && !label.ends_with('-')
+                && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-'));
+        if self.host.parse::<IpAddr>().is_err() && !host_name(&self.host) {
+            return Err(invalid("host", &self.host))
+        }
+        if self.port == 0 {
+            return Err(invalid("port", "0"))
+        }
+        if !(1..=1024).contains(&self.workers) {
+            return Err(invalid("workers", &self.workers.to_string()))
+        }
+        if !LOG_LEVELS.contains(&self.log_level.as_str()) {
+            return Err(invalid("log level", &self.log_level))
+        }
+        Ok(())
+    }
+
+    // host:port for bind() ("[::1]:8080" for IPv6 addresses)
+    pub fn address(&self) -> String {
+        match self.host.parse::<IpAddr>() {
+            Ok(IpAddr::V6(ip)) => format!("[{}]:{}", ip, self.port),
+            _ => format!("{}:{}", self.host, self.port),
+        }
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+}
+
+const SETTINGS: [(&str, &str, &str); 4] = [
+    ("host", "--host", "PLAYSV_HOST"),
+    ("port", "--port", "PLAYSV_PORT"),
+    ("workers", "--workers", "PLAYSV_WORKERS"),
+    ("log_level", "--log-level", "PLAYSV_LOG_LEVEL"),
+];
+
+// args: without the program name, var: e.g. std::env::var(name).ok()
+pub fn load<V>(args: &[String], var: V) -> Result<Options, ConfigError>
+    where V: Fn(&str) -> Option<String>
+{
+    let mut flags: Vec<(&str, &str, &str)> = Vec::new();
+    let mut path = var("PLAYSV_CONFIG");
+    let mut print_config = false;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value)),
+            None => (arg.as_str(), None),
+        };
+        let mut value = || inline.or_else(|| rest.next().map(|s| s.as_str())).ok_or(ConfigError::Usage);
+        match flag {
+            "--print-config" if inline.is_none() => print_config = true,
+            "--config" => path = Some(value()?.to_string()),
+            _ => match SETTINGS.iter().find(|s| s.1 == flag) {
+                Some(&(key, name, _)) => flags.push((key, name, value()?)),
+                None => return Err(ConfigError::Usage),
+            },
+        }
+    }
+
+    let mut config = match path {
+        Some(path) => {
+            let text = fs::read_to_string(&path).map_err(|e| ConfigError::File(path.clone(), e.to_string()))?;
+            toml::from_str(&text).map_err(|e| ConfigError::File(path.clone(), e.to_string()))?
+        }
+        None => Config::default(),
+    };
+    for &(key, _, name) in SETTINGS.iter() {
+        if let Some(value) = var(name) {
+            config.set(key, name, &value)?;
+        }
+    }
+    for (key, name, value) in flags {
+        config.set(key, name, value)?;
+    }
+    // "WARN" from any source
+    config.log_level.make_ascii_lowercase();
+    config.validate()?;
+
+    Ok(Options { config, print_config })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str) -> Vec<String> {
+        text.split_whitespace().map(String::from).collect()
+    }
+
+    fn no_vars(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn precedence() {
+        let path = std::env::temp_dir().join(format!("playsv-test-{}.toml", std::process::id()));
+        fs::write(&path, "host = \"0.0.0.0\"\nport = 9000\nworkers = 2\nlog_level = \"DEBUG\"\n").unwrap();
+        let vars = |name: &str| match name {
+            "PLAYSV_CONFIG" => Some(path.to_string_lossy().into_owned()),
+            "PLAYSV_PORT" => Some("9100".to_string()),
+            "PLAYSV_LOG_LEVEL" => Some("WARN".to_string()),
+            _ => None,
+        };
+
+        let options = load(&args("--port 9200 --workers=3"), vars).unwrap();
+        assert_eq!(options.config, Config {
+            host: "0.0.0.0".to_string(), port: 9200, workers: 3, log_level: "warn".to_string(),
+        });
+        assert!(!options.print_config);
+        let options = load(&args("--print-config"), vars).unwrap();
+        assert_eq!(options.config.port, 9100);
+        assert!(options.print_config);
+        assert_eq!(options.config.to_toml(), "host = \"0.0.0.0\"\nport = 9100\nworkers = 2\nlog_level = \"warn\"\n");
+        let file_only = |name: &str| if name == "PLAYSV_CONFIG" { vars(name) } else { None };
+        assert_eq!(load(&[], file_only).unwrap().config.log_level, "debug");
+        fs::remove_file(&path).unwrap();
+
+        let options = load(&[], no_vars).unwrap();
+        assert_eq!(options.config.address(), "127.0.0.1:8080");
+        let options = load(&args("--host ::1"), no_vars).unwrap();
+        assert_eq!(options.config.address(), "[::1]:8080");
+    }
+
+    #[test]
+    fn errors() {
+        let error = |text: &str| load(&args(text), no_vars).unwrap_err();
+        assert_eq!(error("--port"), ConfigError::Usage);
+        assert_eq!(error("--verbose"), ConfigError::Usage);
+        assert_eq!(error("--port 70000"), invalid("--port", "70000"));
+        assert_eq!(error("--port 0"), invalid("port", "0"));
+        assert_eq!(error("--workers 0"), invalid("workers", "0"));
+        assert_eq!(error("--host bad_host!"), invalid("host", "bad_host!"));
+        assert_eq!(error("--log-level loud"), invalid("log level", "loud"));
+        assert!(matches!(error("--config /nonexistent/playsv.toml"), ConfigError::File(..)));
+        let vars = |name: &str| if name == "PLAYSV_WORKERS" { Some("many".to_string()) } else { None };
+        assert_eq!(load(&[], vars).unwrap_err(), invalid("PLAYSV_WORKERS", "many"));
+
+        let path = std::env::temp_dir().join(format!("playsv-test-bad-{}.toml", std::process::id()));
+        fs::write(&path, "listen = 1\n").unwrap();
+        let error = load(&args(&format!("--config {}", path.display())), no_vars).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert!(error.to_string().contains("unknown field `listen`"));
+    }
+}
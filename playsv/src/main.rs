@@ -1,7 +1,11 @@
+mod api;
+mod config;
+
+use std::env;
+use std::process;
 use git_version::git_version;
-use actix_web::{get, App, HttpResponse, HttpServer, Responder};
-use serde::{Serialize, Deserialize};
-use serde_json;
+use actix_web::{get, middleware, App, HttpResponse, HttpServer, Responder};
+use serde::Serialize;
 
 const GIT_VERSION: &str = git_version!();
 
@@ -29,12 +33,35 @@ async fn info() -> impl Responder {
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("usage: playsv {}", config::USAGE);
+        return Ok(())
+    }
+    let options = config::load(&args, |name| env::var(name).ok()).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    let config = options.config;
+    if options.print_config {
+        print!("{}", config.to_toml());
+        return Ok(())
+    }
+
+    env_logger::Builder::new()
+        .parse_filters(&config.log_level)
+        .init();
+    log::info!("listening on {} with {} workers", config.address(), config.workers);
+
     HttpServer::new(|| {
         App::new()
+            .wrap(middleware::Logger::default())
             .service(hello)
             .service(info)
+            .configure(api::routes)
     })
-    .bind("127.0.0.1:8080")?
+    .workers(config.workers)
+    .bind(config.address())?
     .run()
     .await
 }